@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        common::HexPos,
+        hearing::{propagate, Hearing, Noise},
+        turn_queue::TurnQueue,
+    },
+    map::MapTiles,
+    Player,
+};
+
+// where the player was last heard, and how many turns had been taken by then
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeardNoise {
+    pub coord: Coordinate,
+    pub turn: u32,
+}
+
+pub fn update_heard_noises(
+    mut commands: Commands,
+    mut noises: EventReader<Noise>,
+    listeners: Query<(Entity, &HexPos, &Hearing), Without<Player>>,
+    players: Query<(), With<Player>>,
+    map: MapTiles,
+    turn_queue: Res<TurnQueue>,
+) {
+    let max_acuity = match listeners.iter().map(|(_, _, h)| h.acuity).max() {
+        Some(acuity) => acuity,
+        None => return,
+    };
+    let mut terrain = None;
+
+    for noise in noises.iter() {
+        // enemies only listen out for the player
+        if players.get(noise.entity).is_err() {
+            continue;
+        }
+
+        let terrain = terrain.get_or_insert_with(|| map.get_terrain());
+        let steps = propagate(
+            noise.source,
+            noise.loudness as i32 + max_acuity as i32,
+            terrain,
+        );
+
+        for (e, &HexPos(coord), hearing) in listeners.iter() {
+            if steps
+                .get(&coord)
+                .map_or(false, |&s| hearing.can_hear(noise, s))
+            {
+                commands.entity(e).insert(HeardNoise {
+                    coord: noise.source,
+                    turn: turn_queue.turns_elapsed(),
+                });
+            }
+        }
+    }
+}
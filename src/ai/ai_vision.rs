@@ -1,41 +1,76 @@
 use bevy::prelude::*;
-use bevy_prototype_lyon::prelude::*;
-use hex2d::Position;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     domain::{
-        common::{Facing, HexPos},
-        vision::Vision,
+        common::HexPos, fov::FieldOfView, light::LightMap, los::Sight, turn_queue::TurnQueue,
     },
-    map::MapTiles,
     Player,
 };
 
+use super::awareness::{AlertLevel, Awareness};
+
 #[derive(Component)]
 pub struct CanSeePlayer;
 
+// where the player was when last seen, and how many turns had been taken by then
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastSeen {
+    pub coord: Coordinate,
+    pub turn: u32,
+}
+
+// `CanSeePlayer` is only given to actors which have been alerted; those which are merely
+// suspicious still remember where they glimpsed the player, and so go and look
 pub fn update_can_see_player(
     mut commands: Commands,
-    seers: Query<(Entity, &HexPos, &Facing, &Vision), Without<Player>>,
+    mut seers: Query<(Entity, &HexPos, &FieldOfView, Option<&mut Awareness>), Without<Player>>,
     players: Query<&HexPos, With<Player>>,
-    map: MapTiles,
+    light: Res<LightMap>,
+    turn_queue: Res<TurnQueue>,
+    mut last_turn: Local<Option<u32>>,
 ) {
-    if let Ok(&HexPos(player_pos)) = players.get_single() {
-        let walls = map.get_walls();
+    // awareness changes once per turn, as the turn of the actor it belongs to begins
+    let turn = turn_queue.turns_elapsed();
+    let starting = turn_queue
+        .head()
+        .copied()
+        .filter(|_| *last_turn != Some(turn));
+    *last_turn = Some(turn);
 
-        for (e, &HexPos(coord), &Facing(dir), vis) in seers.iter() {
-            let pos = Position::new(coord, dir);
+    if let Ok(&HexPos(player_pos)) = players.get_single() {
+        for (e, &HexPos(coord), fov, awareness) in seers.iter_mut() {
+            let sight = fov.sight(player_pos);
+            let visible = sight.is_visible();
 
-            let visible = vis.can_see_relative(pos, player_pos, |x| walls.contains(&x));
+            // actors without an awareness meter notice the player the moment they see them
+            let level = match awareness {
+                Some(mut awareness) => {
+                    if starting == Some(e) {
+                        // a player half hidden behind cover is as hard to make out as one in the
+                        // gloom
+                        let lit = match sight {
+                            Sight::Partial => light.level(player_pos) / 2,
+                            _ => light.level(player_pos),
+                        };
+                        let seen = visible.then(|| (coord.distance(player_pos), lit));
+                        awareness.update(seen);
+                    }
+                    awareness.level()
+                }
+                None if visible => AlertLevel::Alerted,
+                None => AlertLevel::Unaware,
+            };
 
-            if visible {
-                commands
-                    .entity(e)
-                    .insert(DrawMode::Outlined {
-                        fill_mode: FillMode::color(Color::RED),
-                        outline_mode: StrokeMode::new(Color::BLACK, 1.0),
-                    })
-                    .insert(CanSeePlayer);
+            if visible && level != AlertLevel::Unaware {
+                commands.entity(e).insert(LastSeen {
+                    coord: player_pos,
+                    turn,
+                });
+            }
+            if visible && level == AlertLevel::Alerted {
+                commands.entity(e).insert(CanSeePlayer);
             } else {
                 commands.entity(e).remove::<CanSeePlayer>();
             }
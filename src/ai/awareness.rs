@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertLevel {
+    Unaware,
+    // has glimpsed something, and will go and look
+    Suspicious,
+    // knows the player is there, and hunts them on sight
+    Alerted,
+}
+
+const MAX_AWARENESS: u8 = 100;
+const SUSPICIOUS_AT: u8 = 35;
+const DECAY_PER_TURN: u8 = 15;
+// the player is noticed faster the closer they are than this
+const NOTICE_DISTANCE: i32 = 6;
+
+// how much of the player an actor has noticed, rising while they stay in sight and falling away
+// again once they are gone
+//
+// once alerted an actor stays that way until it has calmed down to merely suspicious, so that
+// briefly losing sight of the player does not end a chase
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Awareness {
+    pub meter: u8,
+    pub alerted: bool,
+}
+
+impl Awareness {
+    pub fn level(&self) -> AlertLevel {
+        if self.alerted {
+            AlertLevel::Alerted
+        } else if self.meter >= SUSPICIOUS_AT {
+            AlertLevel::Suspicious
+        } else {
+            AlertLevel::Unaware
+        }
+    }
+
+    // one turn's worth of change; `seen` is how far away the player was in sight, along with
+    // how brightly lit they were as a percentage
+    pub fn update(&mut self, seen: Option<(i32, u8)>) {
+        match seen {
+            Some((distance, light)) => {
+                let closeness = (NOTICE_DISTANCE - distance).max(0) as u32;
+                let gain = (15 + 10 * closeness) * light as u32 / 100;
+                self.meter = (self.meter as u32 + gain).min(MAX_AWARENESS as u32) as u8;
+                if self.meter == MAX_AWARENESS {
+                    self.alerted = true;
+                }
+            }
+            None => {
+                self.meter = self.meter.saturating_sub(DECAY_PER_TURN);
+                if self.meter < SUSPICIOUS_AT {
+                    self.alerted = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turns_to_alert(distance: i32, light: u8) -> usize {
+        let mut awareness = Awareness::default();
+        (1..=20)
+            .find(|_| {
+                awareness.update(Some((distance, light)));
+                awareness.level() == AlertLevel::Alerted
+            })
+            .unwrap_or(usize::MAX)
+    }
+
+    #[test]
+    fn nearby_and_well_lit_players_are_noticed_sooner() {
+        assert!(turns_to_alert(1, 100) < turns_to_alert(5, 100));
+        assert!(turns_to_alert(5, 100) < turns_to_alert(5, 50));
+        assert_eq!(turns_to_alert(1, 0), usize::MAX);
+    }
+
+    #[test]
+    fn alertness_lasts_until_calmed_down() {
+        let mut awareness = Awareness::default();
+        awareness.update(Some((2, 100)));
+        assert_eq!(awareness.level(), AlertLevel::Suspicious);
+
+        while awareness.level() != AlertLevel::Alerted {
+            awareness.update(Some((2, 100)));
+        }
+
+        awareness.update(None);
+        assert_eq!(awareness.level(), AlertLevel::Alerted);
+
+        for _ in 0..10 {
+            awareness.update(None);
+        }
+        assert_eq!(awareness.level(), AlertLevel::Unaware);
+    }
+}
@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::{domain::common::Actor, turn_engine::actions::ActionQueue};
+
+use super::{ai_hearing::HeardNoise, ai_vision::LastSeen, nodes};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+}
+
+impl Status {
+    fn from_bool(b: bool) -> Status {
+        if b {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+// a behaviour tree, ticked once whenever its actor needs to plan a turn
+//
+// leaves either check a condition or queue actions; trees are plain data so that each kind of
+// enemy can be given its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BtNode {
+    // succeeds once every child has, stopping at the first failure and dropping whatever the
+    // children before it planned
+    Sequence(Vec<BtNode>),
+    // succeeds as soon as any child does
+    Selector(Vec<BtNode>),
+    Invert(Box<BtNode>),
+
+    // targets the player if they can be seen
+    CanSeePlayer,
+    HasTarget,
+    IsHurt,
+    // succeeds while the player was seen no more than `turns` turns ago, counting every actor's
+    // turns; forgets where they were once it has been longer
+    Remembers { turns: u32 },
+    // as `Remembers`, for where the player was last heard
+    Heard { turns: u32 },
+    // forgets the target along with where the player was last seen or heard
+    ForgetTarget,
+
+    StrikeIfAdjacent,
+    PathToTarget,
+    Flee,
+    // heads for where the player was last seen, failing once there
+    Investigate,
+    // heads for where the player was last heard, failing once there
+    InvestigateNoise,
+    // turns towards where the player was last heard, failing if already facing that way
+    FaceNoise,
+    // looks around on the spot
+    Search,
+    // wanders without straying further than `radius` from where the actor started
+    Patrol { radius: i32 },
+    Wander,
+}
+
+impl BtNode {
+    pub fn tick(&self, ctx: &mut BtContext) -> Status {
+        match self {
+            BtNode::Sequence(children) => {
+                let planned = ctx.plan.len();
+                let status =
+                    Status::from_bool(children.iter().all(|c| c.tick(ctx) == Status::Success));
+                if status == Status::Failure {
+                    ctx.plan.truncate(planned);
+                }
+                status
+            }
+            BtNode::Selector(children) => {
+                Status::from_bool(children.iter().any(|c| c.tick(ctx) == Status::Success))
+            }
+            BtNode::Invert(child) => Status::from_bool(child.tick(ctx) == Status::Failure),
+
+            BtNode::CanSeePlayer => nodes::can_see_player(ctx),
+            BtNode::HasTarget => nodes::has_target(ctx),
+            BtNode::IsHurt => nodes::is_hurt(ctx),
+            BtNode::Remembers { turns } => nodes::remembers::<LastSeen>(ctx, *turns),
+            BtNode::Heard { turns } => nodes::remembers::<HeardNoise>(ctx, *turns),
+            BtNode::ForgetTarget => nodes::forget_target(ctx),
+
+            BtNode::StrikeIfAdjacent => nodes::strike_if_adjacent(ctx),
+            BtNode::PathToTarget => nodes::path_to_target(ctx),
+            BtNode::Flee => nodes::flee(ctx),
+            BtNode::Investigate => nodes::investigate::<LastSeen>(ctx),
+            BtNode::InvestigateNoise => nodes::investigate::<HeardNoise>(ctx),
+            BtNode::FaceNoise => nodes::face_noise(ctx),
+            BtNode::Search => nodes::search(ctx),
+            BtNode::Patrol { radius } => nodes::patrol(ctx, *radius),
+            BtNode::Wander => nodes::wander(ctx),
+        }
+    }
+}
+
+// what an actor remembers between turns
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Blackboard {
+    pub target: Option<Entity>,
+    // where a patrol is centred, set the first time the actor patrols
+    pub home: Option<Coordinate>,
+}
+
+pub struct BtContext<'a> {
+    pub world: &'a mut World,
+    pub entity: Entity,
+    pub blackboard: &'a mut Blackboard,
+    // the actions queued so far this turn
+    pub plan: &'a mut ActionQueue,
+}
+
+impl<'a> BtContext<'a> {
+    // the energy left once everything planned so far has been paid for
+    pub fn energy(&self) -> u8 {
+        let spent: u8 = self.plan.iter().map(|a| a.cost()).sum();
+        self.world
+            .get::<Actor>(self.entity)
+            .map_or(0, |actor| actor.actions_remaining.saturating_sub(spent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::health::Health;
+
+    use super::*;
+
+    fn tick(node: &BtNode, world: &mut World, entity: Entity) -> (Status, ActionQueue) {
+        let mut blackboard = Blackboard::default();
+        let mut plan = ActionQueue::default();
+        let status = node.tick(&mut BtContext {
+            world,
+            entity,
+            blackboard: &mut blackboard,
+            plan: &mut plan,
+        });
+        (status, plan)
+    }
+
+    #[test]
+    fn composites_short_circuit() {
+        let mut world = World::new();
+        let healthy = world.spawn().insert(Health::new(3)).id();
+
+        let (status, _) = tick(
+            &BtNode::Sequence(vec![BtNode::IsHurt, BtNode::ForgetTarget]),
+            &mut world,
+            healthy,
+        );
+        assert_eq!(status, Status::Failure);
+
+        let (status, _) = tick(
+            &BtNode::Selector(vec![BtNode::IsHurt, BtNode::ForgetTarget]),
+            &mut world,
+            healthy,
+        );
+        assert_eq!(status, Status::Success);
+
+        let (status, _) = tick(
+            &BtNode::Invert(Box::new(BtNode::IsHurt)),
+            &mut world,
+            healthy,
+        );
+        assert_eq!(status, Status::Success);
+    }
+
+    #[test]
+    fn failed_sequences_drop_what_they_planned() {
+        let mut world = World::new();
+        let healthy = world.spawn().insert(Health::new(3)).id();
+
+        let (status, plan) = tick(
+            &BtNode::Sequence(vec![BtNode::Search, BtNode::IsHurt]),
+            &mut world,
+            healthy,
+        );
+        assert_eq!(status, Status::Failure);
+        assert!(plan.is_empty());
+
+        let (status, plan) = tick(
+            &BtNode::Selector(vec![
+                BtNode::Sequence(vec![BtNode::Search, BtNode::IsHurt]),
+                BtNode::Search,
+            ]),
+            &mut world,
+            healthy,
+        );
+        assert_eq!(status, Status::Success);
+        assert_eq!(plan.len(), 1);
+    }
+
+    #[test]
+    fn trees_round_trip_through_json() {
+        let tree = BtNode::Selector(vec![
+            BtNode::Sequence(vec![BtNode::CanSeePlayer, BtNode::PathToTarget]),
+            BtNode::Patrol { radius: 3 },
+        ]);
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<BtNode>(&json).unwrap(), tree);
+    }
+}
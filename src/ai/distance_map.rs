@@ -0,0 +1,232 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use bevy::{prelude::*, utils::HashMap};
+use hex2d::{Angle, Coordinate, Direction, Position};
+
+use crate::{
+    domain::common::HexPos,
+    map::{MapTiles, MapVersion, Terrain},
+    pathfinding::{CostModel, Move, MoveCosts, TerrainCosts},
+    Player,
+};
+
+// for every reachable Position, the cost of the cheapest route from there to a goal, along with
+// the first move of that route
+//
+// computed by a single Dijkstra search outwards from the goals, so any number of actors can
+// share it instead of each running their own search
+#[derive(Default)]
+pub struct DistanceMap {
+    costs: HashMap<Position, i32>,
+    next: HashMap<Position, Move>,
+}
+
+const DESCENT_MOVES: [Move; 4] = [
+    Move::StepForward,
+    Move::StepBackward,
+    Move::TurnLeft,
+    Move::TurnRight,
+];
+
+// the position from which `mov` leads to `to`
+fn unapply(mov: Move, to: Position) -> Position {
+    match mov {
+        Move::TurnLeft => to + Angle::Right,
+        Move::TurnRight => to + Angle::Left,
+        Move::StepForward => Position::new(to.coord + (to.dir + Angle::Back), to.dir),
+        Move::StepBackward => Position::new(to.coord + to.dir, to.dir),
+    }
+}
+
+// derived maps value each step of improvement above the cost of the step itself, so that actors
+// will actually move to improve their position
+fn seed_weight(model: &impl CostModel) -> i32 {
+    2 * model.min_step_cost().max(1)
+}
+
+fn all_facings(coord: Coordinate) -> impl Iterator<Item = Position> {
+    Direction::all()
+        .iter()
+        .map(move |&dir| Position::new(coord, dir))
+}
+
+impl DistanceMap {
+    // seeds may start at any cost; positions whose seed cost is never improved upon are where
+    // descent stops
+    pub fn from_seeds(
+        seeds: impl IntoIterator<Item = (Position, i32)>,
+        terrain: &HashMap<Coordinate, Terrain>,
+        model: &impl CostModel,
+    ) -> DistanceMap {
+        let mut costs = HashMap::<Position, i32>::default();
+        let mut next = HashMap::<Position, Move>::default();
+        let mut to_search = BinaryHeap::new();
+
+        for (pos, cost) in seeds {
+            if costs.get(&pos).map_or(true, |&c| cost < c) {
+                costs.insert(pos, cost);
+                to_search.push(Reverse((cost, pos.coord.x, pos.coord.y, pos.dir as i32)));
+            }
+        }
+
+        while let Some(Reverse((cost, x, y, dir))) = to_search.pop() {
+            let current = Position::new(Coordinate::new(x, y), Direction::from_int(dir));
+            if cost > costs[&current] {
+                continue;
+            }
+
+            for mov in DESCENT_MOVES {
+                let from = unapply(mov, current);
+                if !terrain.contains_key(&from.coord) {
+                    continue;
+                }
+                let step = match model.cost(from, mov) {
+                    Some(step) => step,
+                    None => continue,
+                };
+
+                let total = cost + step;
+                if costs.get(&from).map_or(true, |&c| total < c) {
+                    costs.insert(from, total);
+                    next.insert(from, mov);
+                    to_search.push(Reverse((
+                        total,
+                        from.coord.x,
+                        from.coord.y,
+                        from.dir as i32,
+                    )));
+                }
+            }
+        }
+
+        DistanceMap { costs, next }
+    }
+
+    // the cost of reaching any of `goals` from everywhere on the map
+    pub fn approach(
+        goals: impl IntoIterator<Item = Coordinate>,
+        terrain: &HashMap<Coordinate, Terrain>,
+        model: &impl CostModel,
+    ) -> DistanceMap {
+        let seeds = goals.into_iter().flat_map(all_facings).map(|pos| (pos, 0));
+        DistanceMap::from_seeds(seeds, terrain, model)
+    }
+
+    // descends away from this map's goals, preferring the furthest spot over the nearest dead end
+    pub fn flee(
+        &self,
+        terrain: &HashMap<Coordinate, Terrain>,
+        model: &impl CostModel,
+    ) -> DistanceMap {
+        let weight = seed_weight(model);
+        let seeds = self.costs.iter().map(|(&pos, &cost)| (pos, -cost * weight));
+        DistanceMap::from_seeds(seeds, terrain, model)
+    }
+
+    pub fn cost(&self, pos: Position) -> Option<i32> {
+        self.costs.get(&pos).copied()
+    }
+
+    // the moves which lead downhill from `from` until nothing cheaper can be reached
+    pub fn descend(&self, from: Position) -> VecDeque<Move> {
+        let mut path = VecDeque::new();
+        let mut current = from;
+        while let Some(&mov) = self.next.get(&current) {
+            path.push_back(mov);
+            current = mov.apply(current);
+        }
+        path
+    }
+}
+
+// distance maps relative to the player, shared by every AI actor
+#[derive(Default)]
+pub struct PlayerDistanceMaps {
+    key: Option<(Coordinate, MapVersion)>,
+    pub approach: DistanceMap,
+    pub flee: DistanceMap,
+}
+
+pub fn update_player_distance_maps(
+    players: Query<(Entity, &HexPos), With<Player>>,
+    map: MapTiles,
+    version: Res<MapVersion>,
+    mut maps: ResMut<PlayerDistanceMaps>,
+) {
+    let (player, &HexPos(pos)) = match players.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let key = Some((pos, *version));
+    if maps.key == key {
+        return;
+    }
+
+    // every actor's moves currently cost the same, so the player's stand in for everyone's
+    let terrain = map.get_terrain();
+    let model = TerrainCosts {
+        moves: MoveCosts::for_entity(player),
+        terrain: &terrain,
+    };
+
+    let approach = DistanceMap::approach([pos], &terrain, &model);
+    let flee = approach.flee(&terrain, &model);
+    *maps = PlayerDistanceMaps {
+        key,
+        approach,
+        flee,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::open_floor;
+
+    use super::*;
+
+    const COSTS: MoveCosts = MoveCosts {
+        turn: 0,
+        step: 1,
+        backstep: 2,
+    };
+
+    fn end_of(path: &VecDeque<Move>, start: Position) -> Position {
+        path.iter().fold(start, |pos, mov| mov.apply(pos))
+    }
+
+    #[test]
+    fn descending_the_approach_map_reaches_the_goal() {
+        let terrain = open_floor(4);
+        let model = TerrainCosts {
+            moves: COSTS,
+            terrain: &terrain,
+        };
+        let goal = Coordinate::new(2, 1);
+        let map = DistanceMap::approach([goal], &terrain, &model);
+
+        let start = Position::new(Coordinate::new(-2, 0), Direction::XY);
+        let path = map.descend(start);
+
+        assert_eq!(end_of(&path, start).coord, goal);
+        assert_eq!(map.cost(start), Some(start.coord.distance(goal)));
+    }
+
+    #[test]
+    fn fleeing_ends_further_away() {
+        let terrain = open_floor(4);
+        let model = TerrainCosts {
+            moves: COSTS,
+            terrain: &terrain,
+        };
+        let goal = Coordinate::new(0, 0);
+        let approach = DistanceMap::approach([goal], &terrain, &model);
+        let flee = approach.flee(&terrain, &model);
+
+        let start = Position::new(Coordinate::new(1, 0), Direction::YZ);
+        let end = end_of(&flee.descend(start), start);
+
+        assert_eq!(end.coord.distance(goal), 4);
+    }
+}
@@ -1,124 +1,231 @@
+pub mod ai_hearing;
 pub mod ai_vision;
+pub mod awareness;
+pub mod behaviour_tree;
+pub mod distance_map;
+pub mod nodes;
 
 use bevy::prelude::*;
-
-use hex2d::*;
-use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::actions::end_turn::EndTurnAction;
-use crate::domain::actions::rotate::RotateAction;
-use crate::domain::actions::step::StepAction;
-use crate::domain::actions::strike::StrikeAction;
 use crate::domain::common::*;
+use crate::domain::fov::FieldOfViewUpdate;
 use crate::domain::turn_queue::TurnQueue;
-use crate::map::MapTiles;
-use crate::pathfinding::{a_star, Move};
-use crate::turn_engine::actions::ActionQueue;
-use crate::turn_engine::TurnState;
-use crate::Player;
+use crate::pathfinding::{PathCache, Reservations};
+use crate::turn_engine::actions::{ActionQueue, AnyAction};
+use crate::turn_engine::effects::EffectQueue;
+use crate::turn_engine::{ActionRejected, TurnState, TurnSystems, TurnVersion};
 
-use self::ai_vision::{update_can_see_player, CanSeePlayer};
+use self::ai_hearing::update_heard_noises;
+use self::ai_vision::update_can_see_player;
+use self::behaviour_tree::{Blackboard, BtContext, BtNode};
+use self::distance_map::{update_player_distance_maps, PlayerDistanceMaps};
 
 pub struct AiPlugin;
 
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(update_can_see_player.label("update_can_see_player"))
-            .add_system(generate_ai_actions.after("update_can_see_player"));
+        app.add_plugin(AiPerceptionPlugin)
+            .init_resource::<LegalActions>()
+            .init_resource::<PathCache>()
+            .init_resource::<Reservations>()
+            .add_system(update_legal_actions.exclusive_system())
+            .add_system(abandon_rejected_plans)
+            // runs once perception and rejections have been dealt with, and their commands applied
+            .add_system(generate_ai_actions.exclusive_system().at_end());
+    }
+}
+
+// what the AI knows about the world, without the AI making any decisions
+pub struct AiPerceptionPlugin;
+
+impl Plugin for AiPerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerDistanceMaps>()
+            // once fields of view have caught up with this frame's moves, for the AI to plan with next frame
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_can_see_player
+                    .label("update_can_see_player")
+                    .after(FieldOfViewUpdate),
+            )
+            .add_system(update_heard_noises.label("update_heard_noises"))
+            .add_system(update_player_distance_maps.label("update_player_distance_maps"));
+    }
+}
+
+// the behaviour tree which decides what an AI actor does with its turn
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct AIBehaviour(pub BtNode);
+
+impl AIBehaviour {
+    // chases the player on sight, goes looking for anything it hears, and runs once badly hurt
+    pub fn hunter() -> AIBehaviour {
+        use BtNode::*;
+        AIBehaviour::with(Selector(vec![InvestigateNoise, Search]), Wander)
+    }
+
+    // as a hunter, but keeps close to where it started when there is nothing to chase, and only
+    // turns to look at what it hears
+    pub fn sentry() -> AIBehaviour {
+        AIBehaviour::with(BtNode::FaceNoise, BtNode::Patrol { radius: 3 })
+    }
+
+    // mills about, paying no attention to anyone
+    pub fn wanderer() -> AIBehaviour {
+        AIBehaviour(BtNode::Wander)
+    }
+
+    fn with(on_noise: BtNode, idle: BtNode) -> AIBehaviour {
+        use BtNode::*;
+
+        let give_up = Sequence(vec![ForgetTarget, idle]);
+        AIBehaviour(Selector(vec![
+            Sequence(vec![
+                IsHurt,
+                Selector(vec![
+                    // cornered actors fight back
+                    Sequence(vec![CanSeePlayer, Selector(vec![Flee, StrikeIfAdjacent])]),
+                    give_up.clone(),
+                ]),
+            ]),
+            Sequence(vec![
+                CanSeePlayer,
+                Selector(vec![StrikeIfAdjacent, PathToTarget]),
+            ]),
+            // having lost sight of the player, look for them where they were last seen; every actor's
+            // turn counts, so this leaves each a handful of its own turns to search
+            Sequence(vec![
+                Remembers { turns: 20 },
+                Selector(vec![Investigate, Search]),
+            ]),
+            Sequence(vec![Heard { turns: 10 }, on_noise]),
+            give_up,
+        ]))
+    }
+}
+
+// the actions which the AI whose turn it is could take right now, along with their effects
+#[derive(Default)]
+pub struct LegalActions {
+    entity: Option<Entity>,
+    // the state of the game `actions` were worked out in
+    version: Option<TurnVersion>,
+    actions: Vec<(AnyAction, EffectQueue)>,
+}
+
+impl LegalActions {
+    pub fn of(&self, entity: Entity) -> impl Iterator<Item = &(AnyAction, EffectQueue)> {
+        self.actions
+            .iter()
+            .filter(move |_| self.entity == Some(entity))
     }
 }
 
-#[derive(Component)]
-pub enum AIBehaviour {
-    Wandering,
-    Chasing(Entity),
+fn update_legal_actions(world: &mut World) {
+    let idle = matches!(world.get_resource::<TurnState>(), Some(TurnState::Idle));
+    let planning = world
+        .get_resource::<ActionQueue>()
+        .map_or(false, |actions| actions.is_empty());
+
+    let entity = world
+        .get_resource::<TurnQueue>()
+        .and_then(|queue| queue.head().copied())
+        .filter(|&e| world.get::<AIBehaviour>(e).is_some())
+        .filter(|_| idle && planning);
+
+    let version = world.get_resource::<TurnVersion>().copied();
+
+    // only worked out again once the turn passes or something happens
+    let current = world.get_resource::<LegalActions>().map_or(true, |legal| {
+        legal.entity == entity && (entity.is_none() || legal.version == version)
+    });
+    if current {
+        return;
+    }
+
+    let actions = match entity {
+        Some(entity) => world.resource_scope(|world, mut systems: Mut<TurnSystems>| {
+            systems.legal_actions(entity, world)
+        }),
+        None => Vec::new(),
+    };
+
+    if let Some(mut legal) = world.get_resource_mut::<LegalActions>() {
+        *legal = LegalActions {
+            entity,
+            version,
+            actions,
+        };
+    }
 }
 
-pub fn generate_ai_actions(
-    turn_state: Res<TurnState>,
-    mut ai: Query<(
-        &HexPos,
-        &Facing,
-        &Actor,
-        Option<&CanSeePlayer>,
-        &mut AIBehaviour,
-    )>,
-    player: Query<Entity, With<Player>>,
-    targets: Query<&HexPos>,
-    map: MapTiles,
-    turn_queue: Res<TurnQueue>,
+// the rest of a plan was made assuming the rejected action would succeed, so drop it and
+// end the turn rather than acting on a stale plan
+fn abandon_rejected_plans(
+    mut rejections: EventReader<ActionRejected>,
+    ai: Query<(), With<AIBehaviour>>,
     mut actions: ResMut<ActionQueue>,
 ) {
-    if !actions.is_empty() {
+    for rejection in rejections.iter() {
+        let entity = rejection.action.entity();
+        if ai.get(entity).is_ok() {
+            actions.clear();
+            actions.push(EndTurnAction::new(entity));
+        }
+    }
+}
+
+// ticks the behaviour tree of the AI actor whose turn it is, queueing whatever it plans followed by
+// the end of its turn
+pub fn generate_ai_actions(world: &mut World) {
+    let idle = matches!(world.get_resource::<TurnState>(), Some(TurnState::Idle));
+    let planning = world
+        .get_resource::<ActionQueue>()
+        .map_or(false, |actions| actions.is_empty());
+    if !idle || !planning {
+        return;
+    }
+
+    let entity = match world
+        .get_resource::<TurnQueue>()
+        .and_then(|queue| queue.head().copied())
+    {
+        Some(entity) => entity,
+        None => return,
+    };
+    let tree = match world.get::<AIBehaviour>(entity) {
+        Some(AIBehaviour(tree)) => tree.clone(),
+        None => return,
+    };
+    if world
+        .get::<Actor>(entity)
+        .map_or(0, |a| a.actions_remaining)
+        == 0
+    {
         return;
     }
-    if let &TurnState::Idle = turn_state.as_ref() {
-        if let Some(&entity) = turn_queue.head() {
-            if let Ok((&HexPos(pos), &Facing(facing), actor, can_see_player, mut behaviour)) =
-                ai.get_mut(entity)
-            {
-                if actor.actions_remaining == 0 {
-                    return;
-                }
-
-                let position = hex2d::Position::new(pos, facing);
-                match *behaviour {
-                    AIBehaviour::Wandering => {
-                        if can_see_player.is_some() {
-                            if let Ok(target) = player.get_single() {
-                                *behaviour = AIBehaviour::Chasing(target);
-                            }
-                        } else {
-                            let rotation =
-                                Angle::from_int::<i32>(rand::thread_rng().gen_range(1..=6));
-
-                            actions.push(RotateAction::new(entity, rotation));
-                            actions.push(StepAction::new(entity));
-                            actions.push(EndTurnAction::new(entity));
-                        }
-                    }
-                    AIBehaviour::Chasing(target) => {
-                        if let Ok(&HexPos(target_pos)) = targets.get(target) {
-                            let valid_tiles = map.get_floor();
-
-                            if let Some(mut path) =
-                                a_star(position, target_pos, |x| valid_tiles.contains(x))
-                            {
-                                let mut cost = 0;
-                                while cost < actor.actions_remaining {
-                                    if let Some(next) = path.pop_front() {
-                                        match next {
-                                            Move::TurnLeft => {
-                                                cost += actions
-                                                    .push(RotateAction::new(entity, Angle::Left));
-                                            }
-                                            Move::TurnRight => {
-                                                cost += actions
-                                                    .push(RotateAction::new(entity, Angle::Right));
-                                            }
-                                            Move::StepForward => {
-                                                // if this is the last move then we are adjacent to the target
-                                                if path.is_empty() {
-                                                    cost += actions.push(StrikeAction::new(entity));
-                                                } else {
-                                                    cost += actions.push(StepAction::new(entity));
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        break;
-                                    }
-                                }
-                                actions.push(EndTurnAction::new(entity));
-                            } else {
-                                *behaviour = AIBehaviour::Wandering;
-                            }
-                        } else {
-                            *behaviour = AIBehaviour::Wandering;
-                        }
-                    }
-                }
-            }
+
+    // actors which have since left the turn queue no longer need their hex
+    world.resource_scope(|world, mut reservations: Mut<Reservations>| {
+        if let Some(queue) = world.get_resource::<TurnQueue>() {
+            reservations.retain(|e| queue.position(e).is_some());
         }
+    });
+
+    let mut blackboard = world.get::<Blackboard>(entity).cloned().unwrap_or_default();
+    let mut plan = ActionQueue::default();
+    tree.tick(&mut BtContext {
+        world,
+        entity,
+        blackboard: &mut blackboard,
+        plan: &mut plan,
+    });
+    plan.push(EndTurnAction::new(entity));
+
+    world.entity_mut(entity).insert(blackboard);
+    if let Some(mut actions) = world.get_resource_mut::<ActionQueue>() {
+        actions.append(plan);
     }
 }
@@ -0,0 +1,821 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use hex2d::{Angle, Coordinate, Direction, Position};
+use rand::prelude::*;
+
+use crate::{
+    component_index::ComponentIndex,
+    domain::{
+        actions::{
+            backstep::BackstepAction, rotate::RotateAction, step::StepAction, strike::StrikeAction,
+        },
+        common::{Actor, Facing, HexPos},
+        effects::move_entity::MoveEffect,
+        health::Health,
+        turn_queue::TurnQueue,
+    },
+    map::{MapTile, MapVersion, Terrain},
+    pathfinding::{
+        a_star, destination, truncate_at_blocked, Avoiding, Move, MoveCosts, PathCache,
+        Reservations, TerrainCosts,
+    },
+    rng::GameRng,
+    turn_engine::actions::{Action, ActionQueue, AnyAction},
+    Player,
+};
+
+use super::{
+    ai_hearing::HeardNoise,
+    ai_vision::{CanSeePlayer, LastSeen},
+    behaviour_tree::BtContext,
+    behaviour_tree::Status,
+    distance_map::PlayerDistanceMaps,
+    LegalActions,
+};
+
+// the leaves of a behaviour tree; conditions never queue actions, and actions only queue anything
+// when they succeed
+
+fn position(world: &World, entity: Entity) -> Option<Position> {
+    let pos = world.get::<HexPos>(entity)?;
+    let facing = world.get::<Facing>(entity)?;
+    Some(Position::new(pos.0, facing.0))
+}
+
+fn target_coord(ctx: &BtContext) -> Option<Coordinate> {
+    ctx.blackboard
+        .target
+        .and_then(|t| ctx.world.get::<HexPos>(t))
+        .map(|p| p.0)
+}
+
+fn terrain_of(world: &mut World) -> HashMap<Coordinate, Terrain> {
+    world
+        .query::<(&HexPos, &MapTile)>()
+        .iter(world)
+        .map(|(pos, tile)| (pos.0, tile.terrain))
+        .collect()
+}
+
+// other actors, and the hexes they mean to step into next, are in the way
+fn is_blocked(world: &World, entity: Entity, coord: Coordinate) -> bool {
+    let occupied = world
+        .get_resource::<ComponentIndex<HexPos>>()
+        .and_then(|index| index.get_entities(&HexPos(coord)))
+        .map_or(false, |entities| {
+            entities
+                .iter()
+                .any(|&e| e != entity && world.get::<Actor>(e).is_some())
+        });
+
+    occupied
+        || world
+            .get_resource::<Reservations>()
+            .map_or(false, |r| r.is_reserved_for_other(coord, entity))
+}
+
+fn turn_towards(from: Direction, to: Direction) -> Angle {
+    (0..6)
+        .map(Angle::from_int::<i32>)
+        .find(|&a| from + a == to)
+        .unwrap_or(Angle::Forward)
+}
+
+fn reservations(world: &mut World) -> Mut<Reservations> {
+    if world.get_resource::<Reservations>().is_none() {
+        world.insert_resource(Reservations::default());
+    }
+    world.get_resource_mut::<Reservations>().unwrap()
+}
+
+pub fn can_see_player(ctx: &mut BtContext) -> Status {
+    if ctx.world.get::<CanSeePlayer>(ctx.entity).is_none() {
+        return Status::Failure;
+    }
+
+    let player = ctx
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(ctx.world)
+        .next();
+
+    match player {
+        Some(player) => {
+            ctx.blackboard.target = Some(player);
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
+
+pub fn has_target(ctx: &mut BtContext) -> Status {
+    let alive = ctx
+        .blackboard
+        .target
+        .map_or(false, |t| ctx.world.get::<Actor>(t).is_some());
+
+    if alive {
+        Status::Success
+    } else {
+        ctx.blackboard.target = None;
+        Status::Failure
+    }
+}
+
+pub fn is_hurt(ctx: &mut BtContext) -> Status {
+    match ctx.world.get::<Health>(ctx.entity) {
+        Some(health) if (health.current as u16) * 3 <= health.max as u16 => Status::Success,
+        _ => Status::Failure,
+    }
+}
+
+// where and when an actor last noticed the player
+pub trait Memory: Component + Copy {
+    fn coord(&self) -> Coordinate;
+    fn turn(&self) -> u32;
+}
+
+impl Memory for LastSeen {
+    fn coord(&self) -> Coordinate {
+        self.coord
+    }
+
+    fn turn(&self) -> u32 {
+        self.turn
+    }
+}
+
+impl Memory for HeardNoise {
+    fn coord(&self) -> Coordinate {
+        self.coord
+    }
+
+    fn turn(&self) -> u32 {
+        self.turn
+    }
+}
+
+pub fn remembers<M: Memory>(ctx: &mut BtContext, turns: u32) -> Status {
+    let memory = match ctx.world.get::<M>(ctx.entity) {
+        Some(&memory) => memory,
+        None => return Status::Failure,
+    };
+
+    let now = ctx
+        .world
+        .get_resource::<TurnQueue>()
+        .map_or(memory.turn(), |queue| queue.turns_elapsed());
+    if now.saturating_sub(memory.turn()) <= turns {
+        Status::Success
+    } else {
+        ctx.world.entity_mut(ctx.entity).remove::<M>();
+        Status::Failure
+    }
+}
+
+pub fn forget_target(ctx: &mut BtContext) -> Status {
+    ctx.blackboard.target = None;
+    ctx.world
+        .entity_mut(ctx.entity)
+        .remove::<LastSeen>()
+        .remove::<HeardNoise>();
+    Status::Success
+}
+
+pub fn strike_if_adjacent(ctx: &mut BtContext) -> Status {
+    let (pos, target) = match (position(ctx.world, ctx.entity), target_coord(ctx)) {
+        (Some(pos), Some(target)) => (pos, target),
+        _ => return Status::Failure,
+    };
+
+    let dir = match Direction::all().iter().find(|&&d| pos.coord + d == target) {
+        Some(&dir) => dir,
+        None => return Status::Failure,
+    };
+
+    let strike = StrikeAction::new(ctx.entity);
+    if ctx.energy() < strike.cost() {
+        return Status::Failure;
+    }
+
+    let turn = turn_towards(pos.dir, dir);
+    if turn != Angle::Forward {
+        ctx.plan.push(RotateAction::new(ctx.entity, turn));
+    }
+    ctx.plan.push(strike);
+    reservations(ctx.world).release(ctx.entity);
+
+    Status::Success
+}
+
+pub fn path_to_target(ctx: &mut BtContext) -> Status {
+    let entity = ctx.entity;
+    let (start, target, target_pos) = match (
+        position(ctx.world, entity),
+        ctx.blackboard.target,
+        target_coord(ctx),
+    ) {
+        (Some(start), Some(target), Some(target_pos)) => (start, target, target_pos),
+        _ => return Status::Failure,
+    };
+
+    // everyone chasing the player shares one search, rather than each running their own
+    let shared = ctx
+        .world
+        .get_resource::<PlayerDistanceMaps>()
+        .filter(|_| ctx.world.get::<Player>(target).is_some())
+        .filter(|maps| maps.approach.cost(start).is_some())
+        .map(|maps| maps.approach.descend(start));
+
+    // the target itself is not in the way, since that is where the strike lands
+    match route(ctx.world, entity, start, target_pos, shared, true) {
+        Some(path) => {
+            let strike = destination(start, &path).coord == target_pos;
+            follow(ctx, start, path, strike);
+            Status::Success
+        }
+        None => Status::Failure,
+    }
+}
+
+pub fn investigate<M: Memory>(ctx: &mut BtContext) -> Status {
+    let entity = ctx.entity;
+    let (start, goal) = match (position(ctx.world, entity), ctx.world.get::<M>(entity)) {
+        (Some(start), Some(memory)) => (start, memory.coord()),
+        _ => return Status::Failure,
+    };
+    if start.coord == goal {
+        return Status::Failure;
+    }
+
+    match route(ctx.world, entity, start, goal, None, false) {
+        Some(path) if !path.is_empty() => {
+            follow(ctx, start, path, false);
+            Status::Success
+        }
+        _ => Status::Failure,
+    }
+}
+
+pub fn face_noise(ctx: &mut BtContext) -> Status {
+    let (pos, heard) = match (
+        position(ctx.world, ctx.entity),
+        ctx.world.get::<HeardNoise>(ctx.entity),
+    ) {
+        (Some(pos), Some(heard)) => (pos, heard.coord),
+        _ => return Status::Failure,
+    };
+
+    match pos.coord.direction_to_cw(heard) {
+        Some(dir) if dir != pos.dir => {
+            ctx.plan
+                .push(RotateAction::new(ctx.entity, turn_towards(pos.dir, dir)));
+            Status::Success
+        }
+        _ => Status::Failure,
+    }
+}
+
+pub fn search(ctx: &mut BtContext) -> Status {
+    // a turn each time round, so that the actor ends up looking every way in turn
+    ctx.plan.push(RotateAction::new(ctx.entity, Angle::Right));
+    reservations(ctx.world).release(ctx.entity);
+    Status::Success
+}
+
+// the path from `start` to `goal`, taking `known` as given when there is one; other actors are
+// routed around, though only when they are actually in the way
+fn route(
+    world: &mut World,
+    entity: Entity,
+    start: Position,
+    goal: Coordinate,
+    known: Option<VecDeque<Move>>,
+    goal_is_passable: bool,
+) -> Option<VecDeque<Move>> {
+    let moves = MoveCosts::for_entity(entity);
+
+    let mut path = match known {
+        Some(path) => path,
+        None => {
+            let version = world
+                .get_resource::<MapVersion>()
+                .copied()
+                .unwrap_or_default();
+            world.resource_scope(|world, mut cache: Mut<PathCache>| {
+                cache.find(version, start, goal, moves, || terrain_of(world))
+            })?
+        }
+    };
+
+    let blocked = |world: &World, c: Coordinate| {
+        !(goal_is_passable && c == goal) && is_blocked(world, entity, c)
+    };
+    let obstructed = {
+        let world = &*world;
+        truncate_at_blocked(start, &mut path, |c| blocked(world, c))
+    };
+    if !obstructed {
+        return Some(path);
+    }
+
+    let terrain = terrain_of(world);
+    let world = &*world;
+    let model = Avoiding {
+        inner: TerrainCosts {
+            moves,
+            terrain: &terrain,
+        },
+        blocked: |c: Coordinate| blocked(world, c),
+    };
+    Some(a_star(start, goal, &model).unwrap_or(path))
+}
+
+pub fn flee(ctx: &mut BtContext) -> Status {
+    let entity = ctx.entity;
+    let start = match position(ctx.world, entity) {
+        Some(start) => start,
+        None => return Status::Failure,
+    };
+
+    let mut path = match ctx.world.get_resource::<PlayerDistanceMaps>() {
+        Some(maps) => maps.flee.descend(start),
+        None => return Status::Failure,
+    };
+    let world = &*ctx.world;
+    truncate_at_blocked(start, &mut path, |c| is_blocked(world, entity, c));
+
+    if path.is_empty() {
+        return Status::Failure;
+    }
+
+    follow(ctx, start, path, false);
+    Status::Success
+}
+
+pub fn patrol(ctx: &mut BtContext, radius: i32) -> Status {
+    let here = match ctx.world.get::<HexPos>(ctx.entity) {
+        Some(pos) => pos.0,
+        None => return Status::Failure,
+    };
+    let home = *ctx.blackboard.home.get_or_insert(here);
+
+    // an actor which has strayed, e.g. while chasing, may only head back
+    wander_where(ctx, |to| {
+        to.distance(home) <= radius || to.distance(home) < here.distance(home)
+    })
+}
+
+pub fn wander(ctx: &mut BtContext) -> Status {
+    wander_where(ctx, |_| true)
+}
+
+fn wander_where(ctx: &mut BtContext, allowed: impl Fn(Coordinate) -> bool) -> Status {
+    let entity = ctx.entity;
+    reservations(ctx.world).release(entity);
+
+    // only consider moves which will not be rejected
+    let moves: Vec<AnyAction> = match ctx.world.get_resource::<LegalActions>() {
+        Some(legal) => legal
+            .of(entity)
+            .filter(|(_, effects)| {
+                effects
+                    .find::<MoveEffect>()
+                    .map_or(false, |&MoveEffect(_, to)| allowed(to))
+            })
+            .map(|(action, _)| action.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut rng = match ctx.world.get_resource_mut::<GameRng>() {
+        Some(rng) => rng,
+        None => return Status::Failure,
+    };
+
+    if moves.is_empty() || rng.gen_ratio(1, 3) {
+        let rotation = Angle::from_int::<i32>(rng.gen_range(1..=6));
+        ctx.plan.push(RotateAction::new(entity, rotation));
+    } else if let Some(action) = moves.choose(&mut *rng) {
+        ctx.plan.push_any(action.clone());
+    }
+    Status::Success
+}
+
+// queues as much of `path` as can be afforded, claiming the hex it will step into next turn
+fn follow(ctx: &mut BtContext, start: Position, path: VecDeque<Move>, strike: bool) {
+    let energy = ctx.energy();
+    let rest = push_path(ctx.plan, ctx.entity, start, path, energy, strike);
+    reserve_next_step(&mut reservations(ctx.world), ctx.entity, rest);
+}
+
+// queues as much of `path` as `energy` allows, returning where that leaves the actor along with the
+// moves it could not afford; when `strike` is set, the final step is turned into an attack on
+// whoever is standing at the end of the path
+fn push_path(
+    actions: &mut ActionQueue,
+    entity: Entity,
+    start: Position,
+    mut path: VecDeque<Move>,
+    energy: u8,
+    strike: bool,
+) -> (Position, VecDeque<Move>) {
+    let mut pos = start;
+    let mut cost = 0;
+    while cost < energy {
+        if let Some(next) = path.pop_front() {
+            match next {
+                Move::TurnLeft => {
+                    cost += actions.push(RotateAction::new(entity, Angle::Left));
+                }
+                Move::TurnRight => {
+                    cost += actions.push(RotateAction::new(entity, Angle::Right));
+                }
+                // if this is the last move then we are adjacent to the target
+                Move::StepForward if strike && path.is_empty() => {
+                    cost += actions.push(StrikeAction::new(entity));
+                    continue;
+                }
+                Move::StepForward => {
+                    cost += actions.push(StepAction::new(entity));
+                }
+                Move::StepBackward => {
+                    cost += actions.push(BackstepAction::new(entity));
+                }
+            }
+            pos = next.apply(pos);
+        } else {
+            break;
+        }
+    }
+    (pos, path)
+}
+
+// claims the first hex the rest of a plan will step into, so others do not plan to stand there
+fn reserve_next_step(
+    reservations: &mut Reservations,
+    entity: Entity,
+    (from, rest): (Position, VecDeque<Move>),
+) {
+    let mut pos = from;
+    let next = rest.into_iter().find_map(|mov| {
+        pos = mov.apply(pos);
+        Some(pos.coord).filter(|&c| c != from.coord)
+    });
+
+    match next {
+        Some(coord) => reservations.reserve(entity, coord),
+        None => reservations.release(entity),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ai::{
+            behaviour_tree::{Blackboard, BtNode},
+            distance_map::DistanceMap,
+        },
+        map::open_floor,
+        turn_engine::effects::EffectQueue,
+    };
+
+    use super::*;
+
+    // a bare world holding an open hex of floor and the resources the nodes read
+    fn test_world() -> World {
+        let mut world = World::new();
+        for c in Coordinate::new(0, 0).range_iter(4) {
+            world.spawn().insert_bundle((
+                HexPos(c),
+                MapTile {
+                    terrain: Terrain::Floor,
+                },
+            ));
+        }
+        world.insert_resource(ComponentIndex::<HexPos>::default());
+        world.insert_resource(MapVersion::default());
+        world.insert_resource(PathCache::default());
+        world.insert_resource(Reservations::default());
+        world.insert_resource(GameRng::new(1));
+        world
+    }
+
+    fn spawn_actor(world: &mut World, coord: Coordinate, dir: Direction) -> Entity {
+        let entity = world
+            .spawn()
+            .insert_bundle((
+                HexPos(coord),
+                Facing(dir),
+                Actor {
+                    actions_per_turn: 2,
+                    actions_remaining: 2,
+                },
+                Health::new(3),
+            ))
+            .id();
+        world
+            .get_resource_mut::<ComponentIndex<HexPos>>()
+            .unwrap()
+            .update(entity, HexPos(coord));
+        entity
+    }
+
+    fn tick(
+        world: &mut World,
+        node: BtNode,
+        entity: Entity,
+        blackboard: &mut Blackboard,
+    ) -> (Status, Vec<String>) {
+        let mut plan = ActionQueue::default();
+        let status = node.tick(&mut BtContext {
+            world,
+            entity,
+            blackboard,
+            plan: &mut plan,
+        });
+        (status, plan.iter().map(|a| a.describe()).collect())
+    }
+
+    // every position a plan passes through, starting with `start`; strikes stay where they are
+    fn walk(start: Position, plan: &[String]) -> Vec<Position> {
+        let mut positions = vec![start];
+        for action in plan {
+            let pos = *positions.last().unwrap();
+            positions.push(match action.as_str() {
+                "Turn left" => pos + Angle::Left,
+                "Turn right" => pos + Angle::Right,
+                "Step forward" => pos + Coordinate::from(pos.dir),
+                "Step back" => pos + Coordinate::from(pos.dir + Angle::Back),
+                "Strike" => pos,
+                other => panic!("{} is not a move", other),
+            });
+        }
+        positions
+    }
+
+    fn targeting(target: Entity) -> Blackboard {
+        Blackboard {
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_see_player_targets_the_player() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+        let player = spawn_actor(&mut world, Coordinate::new(0, 2), Direction::YZ);
+        world.entity_mut(player).insert(Player);
+
+        let mut blackboard = Blackboard::default();
+        let (status, _) = tick(&mut world, BtNode::CanSeePlayer, guard, &mut blackboard);
+        assert_eq!(status, Status::Failure);
+
+        world.entity_mut(guard).insert(CanSeePlayer);
+        let (status, _) = tick(&mut world, BtNode::CanSeePlayer, guard, &mut blackboard);
+        assert_eq!(status, Status::Success);
+        assert_eq!(blackboard.target, Some(player));
+    }
+
+    #[test]
+    fn has_target_forgets_the_dead() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+        let victim = spawn_actor(&mut world, Coordinate::new(0, 2), Direction::YZ);
+
+        let mut blackboard = targeting(victim);
+        let (status, _) = tick(&mut world, BtNode::HasTarget, guard, &mut blackboard);
+        assert_eq!(status, Status::Success);
+
+        world.despawn(victim);
+        let (status, _) = tick(&mut world, BtNode::HasTarget, guard, &mut blackboard);
+        assert_eq!(status, Status::Failure);
+        assert_eq!(blackboard.target, None);
+    }
+
+    #[test]
+    fn is_hurt_below_a_third_of_health() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+        let mut blackboard = Blackboard::default();
+
+        let (status, _) = tick(&mut world, BtNode::IsHurt, guard, &mut blackboard);
+        assert_eq!(status, Status::Failure);
+
+        world.get_mut::<Health>(guard).unwrap().current = 1;
+        let (status, _) = tick(&mut world, BtNode::IsHurt, guard, &mut blackboard);
+        assert_eq!(status, Status::Success);
+    }
+
+    #[test]
+    fn forget_target_clears_the_blackboard() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+
+        let mut blackboard = targeting(guard);
+        let (status, _) = tick(&mut world, BtNode::ForgetTarget, guard, &mut blackboard);
+        assert_eq!(status, Status::Success);
+        assert_eq!(blackboard.target, None);
+    }
+
+    #[test]
+    fn memories_fade() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+        world.entity_mut(guard).insert(LastSeen {
+            coord: Coordinate::new(2, 0),
+            turn: 10,
+        });
+
+        let mut queue = TurnQueue::default();
+        queue.restore([guard], 15);
+        world.insert_resource(queue);
+
+        let node = BtNode::Remembers { turns: 5 };
+        let (status, _) = tick(&mut world, node.clone(), guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Success);
+
+        world.get_resource_mut::<TurnQueue>().unwrap().cycle();
+        let (status, _) = tick(&mut world, node, guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Failure);
+        assert!(world.get::<LastSeen>(guard).is_none());
+    }
+
+    #[test]
+    fn investigating_heads_for_the_last_sighting_then_searches() {
+        let mut world = test_world();
+        let start = Coordinate::new(0, 0);
+        let guard = spawn_actor(&mut world, start, Direction::YZ);
+        world.entity_mut(guard).insert(LastSeen {
+            coord: start + Direction::YZ + Direction::YZ,
+            turn: 0,
+        });
+        let node = BtNode::Selector(vec![BtNode::Investigate, BtNode::Search]);
+
+        let (status, plan) = tick(&mut world, node.clone(), guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Success);
+        assert_eq!(plan, vec!["Step forward", "Step forward"]);
+
+        world.get_mut::<HexPos>(guard).unwrap().0 = start + Direction::YZ + Direction::YZ;
+        let (status, plan) = tick(&mut world, node, guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Success);
+        assert_eq!(plan, vec!["Turn right"]);
+    }
+
+    #[test]
+    fn facing_noise_turns_once() {
+        let mut world = test_world();
+        let start = Coordinate::new(0, 0);
+        let guard = spawn_actor(&mut world, start, Direction::YZ);
+        world.entity_mut(guard).insert(HeardNoise {
+            coord: start + Direction::ZY + Direction::ZY,
+            turn: 0,
+        });
+
+        let (status, plan) = tick(
+            &mut world,
+            BtNode::FaceNoise,
+            guard,
+            &mut Blackboard::default(),
+        );
+        assert_eq!(status, Status::Success);
+        assert_eq!(plan, vec!["Turn Back"]);
+
+        world.get_mut::<Facing>(guard).unwrap().0 = Direction::ZY;
+        let (status, plan) = tick(
+            &mut world,
+            BtNode::FaceNoise,
+            guard,
+            &mut Blackboard::default(),
+        );
+        assert_eq!(status, Status::Failure);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn strike_if_adjacent_turns_to_face_the_target() {
+        let mut world = test_world();
+        let start = Coordinate::new(0, 0);
+        let guard = spawn_actor(&mut world, start, Direction::YZ);
+        let victim = spawn_actor(&mut world, start + Direction::ZY, Direction::YZ);
+        let distant = spawn_actor(&mut world, Coordinate::new(3, 0), Direction::YZ);
+
+        let (status, plan) = tick(
+            &mut world,
+            BtNode::StrikeIfAdjacent,
+            guard,
+            &mut targeting(victim),
+        );
+        assert_eq!(status, Status::Success);
+        assert_eq!(plan.last().map(String::as_str), Some("Strike"));
+        assert_eq!(plan.len(), 2);
+
+        let (status, plan) = tick(
+            &mut world,
+            BtNode::StrikeIfAdjacent,
+            guard,
+            &mut targeting(distant),
+        );
+        assert_eq!(status, Status::Failure);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn path_to_target_steps_around_other_actors() {
+        let mut world = test_world();
+        let start = Coordinate::new(0, 0);
+        let guard = spawn_actor(&mut world, start, Direction::YZ);
+        let blocker = spawn_actor(&mut world, start + Direction::YZ, Direction::YZ);
+        let victim = spawn_actor(
+            &mut world,
+            start + Direction::YZ + Direction::YZ,
+            Direction::YZ,
+        );
+
+        let (status, plan) = tick(
+            &mut world,
+            BtNode::PathToTarget,
+            guard,
+            &mut targeting(victim),
+        );
+        assert_eq!(status, Status::Success);
+
+        // going straight ahead would walk into the actor in between
+        let route = walk(Position::new(start, Direction::YZ), &plan);
+        assert!(route.iter().any(|pos| pos.coord != start));
+        assert!(route.iter().all(|pos| pos.coord != start + Direction::YZ));
+        let reservations = world.get_resource::<Reservations>().unwrap();
+        assert!(!reservations.is_reserved_for_other(start + Direction::YZ, blocker));
+    }
+
+    #[test]
+    fn flee_needs_distance_maps() {
+        let mut world = test_world();
+        let guard = spawn_actor(&mut world, Coordinate::new(0, 0), Direction::YZ);
+
+        let (status, plan) = tick(&mut world, BtNode::Flee, guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Failure);
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn flee_heads_away_from_the_player() {
+        let mut world = test_world();
+        let player = Coordinate::new(0, 0);
+        let start = Position::new(player + Direction::YZ, Direction::XY);
+        let guard = spawn_actor(&mut world, start.coord, start.dir);
+
+        let terrain = open_floor(4);
+        let model = TerrainCosts {
+            moves: MoveCosts::for_entity(guard),
+            terrain: &terrain,
+        };
+        let approach = DistanceMap::approach([player], &terrain, &model);
+        let flee = approach.flee(&terrain, &model);
+        world.insert_resource(PlayerDistanceMaps::default());
+        let mut maps = world.get_resource_mut::<PlayerDistanceMaps>().unwrap();
+        maps.approach = approach;
+        maps.flee = flee;
+
+        let (status, plan) = tick(&mut world, BtNode::Flee, guard, &mut Blackboard::default());
+        assert_eq!(status, Status::Success);
+
+        let end = *walk(start, &plan).last().unwrap();
+        assert!(end.coord.distance(player) > start.coord.distance(player));
+    }
+
+    #[test]
+    fn patrol_remembers_home_and_stays_near_it() {
+        let mut world = test_world();
+        let start = Coordinate::new(0, 0);
+        let guard = spawn_actor(&mut world, start, Direction::YZ);
+
+        // the only legal move leaves the patrol area
+        let far = Coordinate::new(3, 0);
+        let mut effects = EffectQueue::default();
+        effects.push(MoveEffect::new(guard, far));
+        world.insert_resource(LegalActions {
+            entity: Some(guard),
+            version: None,
+            actions: vec![(StepAction::new(guard).into(), effects)],
+        });
+
+        let mut blackboard = Blackboard::default();
+        for _ in 0..10 {
+            let (status, plan) = tick(
+                &mut world,
+                BtNode::Patrol { radius: 1 },
+                guard,
+                &mut blackboard,
+            );
+            assert_eq!(status, Status::Success);
+            assert!(plan.iter().all(|p| p.starts_with("Turn")));
+        }
+        assert_eq!(blackboard.home, Some(start));
+
+        let (_, plan) = tick(&mut world, BtNode::Wander, guard, &mut blackboard);
+        assert_eq!(plan.len(), 1);
+    }
+}
@@ -1,18 +1,28 @@
 use bevy::prelude::*;
+use hex2d::Coordinate;
 
-use crate::Player;
+use crate::{
+    domain::common::{HexPos, HEX_SPACING},
+    Player,
+};
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup)
+        app.init_resource::<HoveredHex>()
+            .add_startup_system(setup)
+            .add_system(update_hovered_hex)
             .add_system_to_stage(CoreStage::PostUpdate, follow_player);
     }
 }
 
 #[derive(Component)]
-struct MainCamera;
+pub struct MainCamera;
+
+// the tile under the mouse cursor, or under the most recent touch
+#[derive(Default)]
+pub struct HoveredHex(pub Option<Coordinate>);
 
 fn setup(mut commands: Commands) {
     commands
@@ -34,3 +44,56 @@ fn follow_player(
         }
     }
 }
+
+// converts a point in window coordinates (origin bottom-left) to the hex drawn beneath it
+pub fn window_to_hex(window_size: Vec2, camera: &GlobalTransform, point: Vec2) -> Coordinate {
+    // the 2d camera is centred on its transform
+    let offset = point - window_size / 2.0;
+    let world = camera.compute_matrix() * offset.extend(0.0).extend(1.0);
+    HexPos::from_translation(world.truncate(), HEX_SPACING).0
+}
+
+fn update_hovered_hex(
+    windows: Res<Windows>,
+    touches: Res<Touches>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut hovered: ResMut<HoveredHex>,
+) {
+    let (window, camera) = match (windows.get_primary(), cameras.get_single()) {
+        (Some(window), Ok(camera)) => (window, camera),
+        _ => return,
+    };
+
+    let point = window
+        .cursor_position()
+        .or_else(|| touches.iter().next().map(|touch| touch.position()));
+
+    if let Some(point) = point {
+        let size = Vec2::new(window.width(), window.height());
+        let hex = Some(window_to_hex(size, camera, point));
+        if hovered.0 != hex {
+            hovered.0 = hex;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_points_pick_the_hex_drawn_beneath() {
+        let size = Vec2::new(800.0, 600.0);
+        let target = Coordinate::new(3, -1);
+        let camera = GlobalTransform::from_translation(HexPos(target).as_translation(HEX_SPACING));
+
+        assert_eq!(window_to_hex(size, &camera, size / 2.0), target);
+
+        // a neighbouring hex is offset on screen as it is on the map, with the y-axis flipped
+        let neighbour = target + hex2d::Direction::XY;
+        let (nx, ny) = neighbour.to_pixel(HEX_SPACING);
+        let (tx, ty) = target.to_pixel(HEX_SPACING);
+        let point = size / 2.0 + Vec2::new(nx - tx, ty - ny);
+        assert_eq!(window_to_hex(size, &camera, point), neighbour);
+    }
+}
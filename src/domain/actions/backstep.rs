@@ -1,16 +1,20 @@
 use crate::{
+    domain::actions::{rejection::RejectionReason, step::move_onto},
     domain::common::*,
-    domain::effects::{energy_cost::EnergyCostEffect, move_entity::MoveEffect},
-    map::{MapTile, Terrain},
+    map::MapTile,
     turn_engine::{
-        actions::{Action, ActionQueue, ActionResult, AnyActionError},
-        effects::EffectQueue,
+        actions::{Action, ActionQueue, ActionResult},
+        entity_serde,
     },
 };
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct BackstepAction(Entity);
+// stepping back carefully is quieter than stepping forward
+const BACKSTEP_LOUDNESS: u8 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackstepAction(#[serde(with = "entity_serde")] Entity);
 
 impl BackstepAction {
     pub fn new(entity: Entity) -> BackstepAction {
@@ -18,9 +22,17 @@ impl BackstepAction {
     }
 }
 impl Action for BackstepAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
     fn cost(&self) -> u8 {
         2
     }
+
+    fn describe(&self) -> String {
+        "Step back".to_string()
+    }
 }
 
 pub fn generator(In(e): In<Entity>) -> ActionQueue {
@@ -30,26 +42,28 @@ pub fn generator(In(e): In<Entity>) -> ActionQueue {
 pub fn handler(
     In(action): In<BackstepAction>,
     actor: Query<(&Actor, &HexPos, &Facing)>,
-    occupied: Query<&HexPos, With<Actor>>,
+    occupied: Query<(Entity, &HexPos), With<Actor>>,
     map_tiles: Query<(&HexPos, &MapTile)>,
 ) -> ActionResult {
     let entity = action.0;
     let cost = action.cost();
     let (actor, pos, facing) = actor
-        .get(entity)?;
+        .get(entity)
+        .map_err(|_| RejectionReason::EntityMissing(entity))?;
     let to = pos.get_facing(-facing.0);
     if actor.actions_remaining < cost {
-        return AnyActionError::res_generic("Not enough action points");
+        return Err(RejectionReason::InsufficientEnergy {
+            required: cost,
+            available: actor.actions_remaining,
+        }
+        .into());
     }
-    if occupied.iter().any(|x| x.0 == to) {
-        return AnyActionError::res_generic("Destination occupied");
+    if let Some((occupant, _)) = occupied.iter().find(|(_, x)| x.0 == to) {
+        return Err(RejectionReason::DestinationOccupied { occupant }.into());
     }
-    if !map_tiles
+    let terrain = map_tiles
         .iter()
-        .any(|(x, tile)| x.0 == to && tile.terrain == Terrain::Floor)
-    {
-        return AnyActionError::res_generic("Destination not floor tile");
-    }
-
-    Ok(EffectQueue::new(EnergyCostEffect::new(entity, cost)).then(MoveEffect::new(entity, to)))
+        .find(|(x, _)| x.0 == to)
+        .map(|(_, tile)| tile.terrain);
+    move_onto(entity, actor, to, terrain, cost, BACKSTEP_LOUDNESS)
 }
@@ -3,12 +3,14 @@ use crate::{
     turn_engine::{
         actions::{Action, ActionQueue, ActionResult},
         effects::EffectQueue,
+        entity_serde,
     },
 };
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct EndTurnAction(Entity);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndTurnAction(#[serde(with = "entity_serde")] Entity);
 
 impl EndTurnAction {
     pub fn new(entity: Entity) -> EndTurnAction {
@@ -17,9 +19,17 @@ impl EndTurnAction {
 }
 
 impl Action for EndTurnAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
     fn cost(&self) -> u8 {
         0
     }
+
+    fn describe(&self) -> String {
+        "End turn".to_string()
+    }
 }
 
 pub fn generator(In(e): In<Entity>) -> ActionQueue {
@@ -4,7 +4,9 @@ use crate::turn_engine::TurnSystems;
 
 pub mod backstep;
 pub mod end_turn;
+pub mod rejection;
 pub mod rotate;
+pub mod shoot;
 pub mod step;
 pub mod strike;
 
@@ -20,6 +22,70 @@ fn setup(mut systems: ResMut<TurnSystems>) {
     systems.register_action_handler(backstep::handler);
     systems.register_action_handler(end_turn::handler);
     systems.register_action_handler(rotate::handler);
+    systems.register_action_handler(shoot::handler);
     systems.register_action_handler(step::handler);
     systems.register_action_handler(strike::handler);
+
+    systems.register_action_generator(step::generator);
+    systems.register_action_generator(backstep::generator);
+    systems.register_action_generator(rotate::generator);
+    systems.register_action_generator(strike::generator);
+    systems.register_action_generator(shoot::generator);
+    systems.register_action_generator(end_turn::generator);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ai::AiPlugin,
+        domain::{
+            common::Actor,
+            effects::{face::FaceEffect, move_entity::MoveEffect},
+        },
+        Player, RunParams, Scenario, SimulationPlugins,
+    };
+
+    use super::*;
+
+    #[test]
+    fn exhausted_actor_may_only_turn_or_end_turn() {
+        let mut app = App::new();
+        app.add_plugins_with(SimulationPlugins, |group| group.disable::<AiPlugin>());
+        RunParams {
+            scenario: Scenario::Arena,
+            seed: Some(11),
+        }
+        .configure(&mut app);
+        app.update();
+
+        let player = app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(&app.world)
+            .next()
+            .unwrap();
+        app.world
+            .get_mut::<Actor>(player)
+            .unwrap()
+            .actions_remaining = 0;
+
+        let legal = app
+            .world
+            .resource_scope(|world, mut systems: Mut<TurnSystems>| {
+                systems.legal_actions(player, world)
+            });
+
+        let kinds: Vec<String> = legal.iter().map(|(action, _)| action.describe()).collect();
+        assert_eq!(kinds, ["Turn left", "Turn right", "End turn"]);
+        assert!(legal
+            .iter()
+            .all(|(_, effects)| effects.find::<MoveEffect>().is_none()));
+        assert_eq!(
+            legal
+                .iter()
+                .filter(|(_, effects)| effects.find::<FaceEffect>().is_some())
+                .count(),
+            2
+        );
+    }
 }
@@ -0,0 +1,44 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{map::Terrain, turn_engine::actions::ActionError};
+
+// why a domain action handler refused an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectionReason {
+    InsufficientEnergy { required: u8, available: u8 },
+    DestinationOccupied { occupant: Entity },
+    // `terrain` is `None` when the destination is off the map
+    DestinationNotWalkable { terrain: Option<Terrain> },
+    EntityMissing(Entity),
+    NoRangedWeapon,
+    OutOfAmmo,
+    OutOfRange { distance: i32, range: i32 },
+}
+
+impl ActionError for RejectionReason {}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::InsufficientEnergy {
+                required,
+                available,
+            } => write!(f, "Not enough energy ({available}/{required})"),
+            RejectionReason::DestinationOccupied { .. } => write!(f, "Something is in the way"),
+            RejectionReason::DestinationNotWalkable {
+                terrain: Some(terrain),
+            } => write!(f, "Cannot walk onto {terrain:?}"),
+            RejectionReason::DestinationNotWalkable { terrain: None } => {
+                write!(f, "Cannot leave the map")
+            }
+            RejectionReason::EntityMissing(_) => write!(f, "Actor no longer exists"),
+            RejectionReason::NoRangedWeapon => write!(f, "Nothing to shoot with"),
+            RejectionReason::OutOfAmmo => write!(f, "Out of ammunition"),
+            RejectionReason::OutOfRange { distance, range } => {
+                write!(f, "Target out of range ({distance}/{range})")
+            }
+        }
+    }
+}
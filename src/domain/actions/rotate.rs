@@ -1,16 +1,20 @@
 use crate::{
+    domain::actions::rejection::RejectionReason,
     domain::common::*,
     domain::effects::face::FaceEffect,
     turn_engine::{
         actions::{Action, ActionQueue, ActionResult},
         effects::EffectQueue,
+        entity_serde,
     },
 };
 use bevy::prelude::*;
 use hex2d::Angle;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RotateAction {
+    #[serde(with = "entity_serde")]
     entity: Entity,
     angle: Angle,
 }
@@ -22,9 +26,21 @@ impl RotateAction {
 }
 
 impl Action for RotateAction {
+    fn entity(&self) -> Entity {
+        self.entity
+    }
+
     fn cost(&self) -> u8 {
         0
     }
+
+    fn describe(&self) -> String {
+        match self.angle {
+            Angle::Left => "Turn left".to_string(),
+            Angle::Right => "Turn right".to_string(),
+            angle => format!("Turn {angle:?}"),
+        }
+    }
 }
 
 pub fn generator(In(e): In<Entity>) -> ActionQueue {
@@ -35,7 +51,9 @@ pub fn generator(In(e): In<Entity>) -> ActionQueue {
 }
 
 pub fn handler(In(action): In<RotateAction>, query: Query<&Facing>) -> ActionResult {
-    let facing = query.get(action.entity)?;
+    let facing = query
+        .get(action.entity)
+        .map_err(|_| RejectionReason::EntityMissing(action.entity))?;
     let target = facing.rotated(action.angle);
     return Ok(EffectQueue::new(FaceEffect::new(action.entity, target)));
 }
@@ -0,0 +1,149 @@
+use crate::{
+    ai::awareness::{AlertLevel, Awareness},
+    domain::actions::rejection::RejectionReason,
+    domain::combat::{attack_damage, line_of_fire, Ammo, AttackSide, RangedWeapon},
+    domain::common::{Actor, Facing, HexPos},
+    domain::effects::{
+        damage::DamageEffect, energy_cost::EnergyCostEffect, noise::NoiseEffect,
+        set_ammo::SetAmmoEffect,
+    },
+    domain::fov::FieldOfView,
+    domain::health::DamageType,
+    map::MapTile,
+    turn_engine::{
+        actions::{Action, ActionQueue, ActionResult},
+        effects::EffectQueue,
+        entity_serde,
+    },
+    Player,
+};
+use bevy::prelude::*;
+use hex2d::{Coordinate, Position};
+use serde::{Deserialize, Serialize};
+
+const SHOT_LOUDNESS: u8 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShootAction {
+    #[serde(with = "entity_serde")]
+    entity: Entity,
+    target: Coordinate,
+}
+
+impl ShootAction {
+    pub fn new(entity: Entity, target: Coordinate) -> ShootAction {
+        ShootAction { entity, target }
+    }
+
+    pub fn target(&self) -> Coordinate {
+        self.target
+    }
+}
+
+impl Action for ShootAction {
+    fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    fn cost(&self) -> u8 {
+        1
+    }
+
+    fn describe(&self) -> String {
+        format!("Shoot at ({}, {})", self.target.x, self.target.y)
+    }
+}
+
+// every other actor the shooter can see and reach
+pub fn generator(
+    In(e): In<Entity>,
+    shooters: Query<(&HexPos, &FieldOfView, &RangedWeapon)>,
+    targets: Query<(Entity, &HexPos), With<Actor>>,
+) -> ActionQueue {
+    let mut actions = ActionQueue::default();
+
+    if let Ok((&HexPos(coord), fov, weapon)) = shooters.get(e) {
+        let mut visible: Vec<Coordinate> = targets
+            .iter()
+            .map(|(target, pos)| (target, pos.0))
+            .filter(|&(target, c)| {
+                target != e && coord.distance(c) <= weapon.range && fov.can_see(c)
+            })
+            .map(|(_, c)| c)
+            .collect();
+        visible.sort_by_key(|c| (coord.distance(*c), c.x, c.y));
+
+        actions.extend(visible.into_iter().map(|c| ShootAction::new(e, c)));
+    }
+
+    actions
+}
+
+pub fn handler(
+    In(action): In<ShootAction>,
+    shooters: Query<(&Actor, &HexPos, Option<&RangedWeapon>, Option<&Ammo>)>,
+    targets: Query<(Entity, &HexPos, &Facing), With<Actor>>,
+    map_tiles: Query<(&HexPos, &MapTile)>,
+    players: Query<(), With<Player>>,
+    awareness: Query<&Awareness>,
+) -> ActionResult {
+    let entity = action.entity;
+    let cost = action.cost();
+    let (actor, &HexPos(from), weapon, ammo) = shooters
+        .get(entity)
+        .map_err(|_| RejectionReason::EntityMissing(entity))?;
+
+    let weapon = weapon.ok_or(RejectionReason::NoRangedWeapon)?;
+    let ammo = ammo
+        .filter(|a| a.current > 0)
+        .ok_or(RejectionReason::OutOfAmmo)?;
+
+    if actor.actions_remaining < cost {
+        return Err(RejectionReason::InsufficientEnergy {
+            required: cost,
+            available: actor.actions_remaining,
+        }
+        .into());
+    }
+
+    let distance = from.distance(action.target);
+    if distance == 0 || distance > weapon.range {
+        return Err(RejectionReason::OutOfRange {
+            distance,
+            range: weapon.range,
+        }
+        .into());
+    }
+
+    let actor_at = |c: Coordinate| {
+        targets
+            .iter()
+            .find(|(e, pos, _)| *e != entity && pos.0 == c)
+    };
+    let is_solid = |c: Coordinate| {
+        map_tiles
+            .iter()
+            .any(|(pos, tile)| pos.0 == c && tile.terrain.properties().is_solid())
+    };
+
+    let mut effects = EffectQueue::new(EnergyCostEffect::new(entity, cost))
+        .then(SetAmmoEffect::new(entity, ammo.current - 1))
+        .then(NoiseEffect::new(entity, from, SHOT_LOUDNESS));
+
+    // the shot flies until it hits a wall or the first actor in its way
+    let line = line_of_fire(from, action.target, |c| {
+        is_solid(c) || actor_at(c).is_some()
+    });
+    if let Some((hit, &HexPos(at), &Facing(facing))) = line.last().and_then(|&c| actor_at(c)) {
+        let side = AttackSide::of(Position::new(at, facing), from);
+        let unaware = players.get(entity).is_ok()
+            && awareness
+                .get(hit)
+                .map_or(true, |a| a.level() != AlertLevel::Alerted);
+        let damage = attack_damage(weapon.damage, side, unaware);
+
+        effects.push(DamageEffect::new(hit, damage, DamageType::Piercing));
+    }
+
+    Ok(effects)
+}
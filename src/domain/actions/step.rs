@@ -1,16 +1,22 @@
 use crate::{
+    domain::actions::rejection::RejectionReason,
     domain::common::*,
-    domain::effects::{energy_cost::EnergyCostEffect, move_entity::MoveEffect},
-    map::{MapTile, Terrain},
+    domain::effects::{energy_cost::EnergyCostEffect, move_entity::MoveEffect, noise::NoiseEffect},
+    map::{MapTile, OnEnter, Terrain},
     turn_engine::{
-        actions::{Action, ActionQueue, ActionResult, AnyActionError},
+        actions::{Action, ActionQueue, ActionResult},
         effects::EffectQueue,
+        entity_serde,
     },
 };
 use bevy::prelude::*;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct StepAction(pub Entity);
+const STEP_LOUDNESS: u8 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepAction(#[serde(with = "entity_serde")] pub Entity);
 
 impl StepAction {
     pub fn new(entity: Entity) -> StepAction {
@@ -18,9 +24,17 @@ impl StepAction {
     }
 }
 impl Action for StepAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
     fn cost(&self) -> u8 {
         1
     }
+
+    fn describe(&self) -> String {
+        "Step forward".to_string()
+    }
 }
 
 pub fn generator(In(e): In<Entity>) -> ActionQueue {
@@ -30,27 +44,156 @@ pub fn generator(In(e): In<Entity>) -> ActionQueue {
 pub fn handler(
     In(action): In<StepAction>,
     actor: Query<(&Actor, &HexPos, &Facing)>,
-    occupied: Query<&HexPos, With<Actor>>,
+    occupied: Query<(Entity, &HexPos), With<Actor>>,
     map_tiles: Query<(&HexPos, &MapTile)>,
 ) -> ActionResult {
     let entity = action.0;
     let cost = action.cost();
-    let (actor, pos, facing) = actor.get(entity)?;
+    let (actor, pos, facing) = actor
+        .get(entity)
+        .map_err(|_| RejectionReason::EntityMissing(entity))?;
 
     let to = pos.get_facing(facing.0);
     if actor.actions_remaining < cost {
-        return AnyActionError::res_generic("Insufficient actions");
+        return Err(RejectionReason::InsufficientEnergy {
+            required: cost,
+            available: actor.actions_remaining,
+        }
+        .into());
     }
-    if occupied.iter().any(|x| x.0 == to) {
-        return AnyActionError::res_generic("Destination occupied");
+    if let Some((occupant, _)) = occupied.iter().find(|(_, x)| x.0 == to) {
+        return Err(RejectionReason::DestinationOccupied { occupant }.into());
     }
 
-    if !map_tiles
+    let terrain = map_tiles
         .iter()
-        .any(|(x, tile)| x.0 == to && tile.terrain == Terrain::Floor)
-    {
-        return AnyActionError::res_generic("Destination not floor");
+        .find(|(x, _)| x.0 == to)
+        .map(|(_, tile)| tile.terrain);
+    move_onto(entity, actor, to, terrain, cost, STEP_LOUDNESS)
+}
+
+// moves onto `to` for `cost` energy on top of whatever its terrain charges, setting off anything the
+// terrain does to those who enter it
+pub(super) fn move_onto(
+    entity: Entity,
+    actor: &Actor,
+    to: Coordinate,
+    terrain: Option<Terrain>,
+    cost: u8,
+    loudness: u8,
+) -> ActionResult {
+    let properties = match terrain.map(Terrain::properties) {
+        Some(properties) if properties.is_walkable() => properties,
+        _ => return Err(RejectionReason::DestinationNotWalkable { terrain }.into()),
+    };
+
+    let cost = cost + properties.move_cost.unwrap_or(0) as u8;
+    if actor.actions_remaining < cost {
+        return Err(RejectionReason::InsufficientEnergy {
+            required: cost,
+            available: actor.actions_remaining,
+        }
+        .into());
+    }
+
+    let mut effects = EffectQueue::new(EnergyCostEffect::new(entity, cost))
+        .then(MoveEffect::new(entity, to))
+        .then(NoiseEffect::new(entity, to, loudness));
+    match properties.on_enter {
+        Some(OnEnter::Noise(loudness)) => effects.push(NoiseEffect::new(entity, to, loudness)),
+        Some(OnEnter::Exhaust) => effects.push(EnergyCostEffect::new(entity, u8::MAX)),
+        None => {}
     }
 
-    Ok(EffectQueue::new(EnergyCostEffect::new(entity, cost)).then(MoveEffect::new(entity, to)))
+    Ok(effects)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::System;
+
+    use super::*;
+
+    fn rejection(world: &mut World, entity: Entity) -> Option<RejectionReason> {
+        let mut system = handler.system();
+        system.initialize(world);
+        let error = system.run(StepAction::new(entity), world).err()?;
+        error.downcast_ref::<RejectionReason>().cloned()
+    }
+
+    fn spawn_actor(world: &mut World, coord: Coordinate, actions_remaining: u8) -> Entity {
+        world
+            .spawn()
+            .insert_bundle((
+                HexPos(coord),
+                Facing::default(),
+                Actor {
+                    actions_per_turn: 2,
+                    actions_remaining,
+                },
+            ))
+            .id()
+    }
+
+    fn spawn_tile(world: &mut World, coord: Coordinate, terrain: Terrain) {
+        world
+            .spawn()
+            .insert_bundle((HexPos(coord), MapTile { terrain }));
+    }
+
+    #[test]
+    fn step_rejections_are_typed() {
+        let mut world = World::new();
+        let origin = Coordinate::new(0, 0);
+        let ahead = origin + Facing::default().0;
+
+        let tired = spawn_actor(&mut world, origin, 0);
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::InsufficientEnergy {
+                required: 1,
+                available: 0
+            })
+        );
+
+        world.get_mut::<Actor>(tired).unwrap().actions_remaining = 2;
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::DestinationNotWalkable { terrain: None })
+        );
+
+        spawn_tile(&mut world, ahead, Terrain::Wall);
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::DestinationNotWalkable {
+                terrain: Some(Terrain::Wall)
+            })
+        );
+
+        let occupant = spawn_actor(&mut world, ahead, 2);
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::DestinationOccupied { occupant })
+        );
+
+        // rubble costs an extra action to clamber over
+        world.despawn(occupant);
+        world.get_mut::<Actor>(tired).unwrap().actions_remaining = 1;
+        for mut tile in world.query::<&mut MapTile>().iter_mut(&mut world) {
+            tile.terrain = Terrain::Rubble;
+        }
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::InsufficientEnergy {
+                required: 2,
+                available: 1
+            })
+        );
+
+        world.despawn(tired);
+        assert_eq!(
+            rejection(&mut world, tired),
+            Some(RejectionReason::EntityMissing(tired))
+        );
+    }
 }
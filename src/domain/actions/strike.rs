@@ -1,15 +1,26 @@
 use crate::{
+    ai::awareness::{AlertLevel, Awareness},
+    domain::actions::rejection::RejectionReason,
+    domain::combat::{attack_damage, AttackSide},
     domain::common::{Actor, Facing, HexPos},
-    domain::effects::{energy_cost::EnergyCostEffect, kill::KillEffect},
+    domain::effects::{damage::DamageEffect, energy_cost::EnergyCostEffect, noise::NoiseEffect},
+    domain::health::DamageType,
     turn_engine::{
-        actions::{Action, ActionQueue, ActionResult, AnyActionError},
+        actions::{Action, ActionQueue, ActionResult},
         effects::EffectQueue,
+        entity_serde,
     },
+    Player,
 };
 use bevy::prelude::*;
+use hex2d::Position;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct StrikeAction(Entity);
+const STRIKE_DAMAGE: u8 = 2;
+const STRIKE_LOUDNESS: u8 = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrikeAction(#[serde(with = "entity_serde")] Entity);
 
 impl StrikeAction {
     pub fn new(attacker: Entity) -> StrikeAction {
@@ -18,9 +29,17 @@ impl StrikeAction {
 }
 
 impl Action for StrikeAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
     fn cost(&self) -> u8 {
         1
     }
+
+    fn describe(&self) -> String {
+        "Strike".to_string()
+    }
 }
 
 pub fn generator(In(e): In<Entity>) -> ActionQueue {
@@ -30,22 +49,39 @@ pub fn generator(In(e): In<Entity>) -> ActionQueue {
 pub fn handler(
     In(action): In<StrikeAction>,
     query: Query<(&HexPos, &Facing, &Actor)>,
-    targets: Query<(&HexPos, Entity), With<Actor>>,
+    targets: Query<(&HexPos, &Facing, Entity), With<Actor>>,
+    players: Query<(), With<Player>>,
+    awareness: Query<&Awareness>,
 ) -> ActionResult {
     let attacker = action.0;
     let cost = action.cost();
-    let (pos, facing, actor) = query.get(attacker)?;
+    let (pos, facing, actor) = query
+        .get(attacker)
+        .map_err(|_| RejectionReason::EntityMissing(attacker))?;
 
-    if actor.actions_remaining <= 0 {
-        return AnyActionError::res_generic("Insufficient action points");
+    if actor.actions_remaining < cost {
+        return Err(RejectionReason::InsufficientEnergy {
+            required: cost,
+            available: actor.actions_remaining,
+        }
+        .into());
     }
 
     let coord_to_attack = pos.get_facing(facing.0);
-    let mut effects = EffectQueue::new(EnergyCostEffect::new(attacker, cost));
+    let mut effects = EffectQueue::new(EnergyCostEffect::new(attacker, cost))
+        .then(NoiseEffect::new(attacker, pos.0, STRIKE_LOUDNESS));
+
+    for (target_pos, target_facing, e) in targets.iter() {
+        if target_pos.0 == coord_to_attack {
+            let side = AttackSide::of(Position::new(target_pos.0, target_facing.0), pos.0);
+            // only enemies keep track of whether they have noticed the player
+            let unaware = players.get(attacker).is_ok()
+                && awareness
+                    .get(e)
+                    .map_or(true, |a| a.level() != AlertLevel::Alerted);
+            let damage = attack_damage(STRIKE_DAMAGE, side, unaware);
 
-    for (pos, e) in targets.iter() {
-        if pos.0 == coord_to_attack {
-            effects.push(KillEffect::new(e));
+            effects.push(DamageEffect::new(e, damage, DamageType::Slashing));
         }
     }
 
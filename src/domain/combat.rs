@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use hex2d::{Coordinate, Position};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::vision::translate_to_relative,
+    maths::{radians_from_yz, RADIANS_150DEG, RADIANS_90DEG},
+};
+
+// which side of the defender an attack lands on, relative to the way it is facing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackSide {
+    Front,
+    Flank,
+    Rear,
+}
+
+impl AttackSide {
+    pub fn of(defender: Position, attacker: Coordinate) -> AttackSide {
+        let angle = radians_from_yz(translate_to_relative(defender, attacker)).abs();
+
+        // neighbouring hexes are 60 degrees apart, so split between them to stay clear of rounding
+        if angle < RADIANS_90DEG {
+            AttackSide::Front
+        } else if angle < RADIANS_150DEG {
+            AttackSide::Flank
+        } else {
+            AttackSide::Rear
+        }
+    }
+}
+
+// `unaware` defenders have not noticed their attacker, and take sneak attack damage
+pub fn attack_damage(base: u8, side: AttackSide, unaware: bool) -> u8 {
+    let damage = match side {
+        AttackSide::Front => base,
+        AttackSide::Flank => base.saturating_add(1),
+        AttackSide::Rear => base.saturating_mul(2),
+    };
+
+    if unaware {
+        damage.saturating_mul(2)
+    } else {
+        damage
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub damage: u8,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ammo {
+    pub current: u8,
+    pub max: u8,
+}
+
+impl Ammo {
+    pub fn new(max: u8) -> Ammo {
+        Ammo { current: max, max }
+    }
+}
+
+// the hexes a shot passes through on its way to `to`, ending early at the first one which stops it
+pub fn line_of_fire(
+    from: Coordinate,
+    to: Coordinate,
+    stops: impl Fn(Coordinate) -> bool,
+) -> Vec<Coordinate> {
+    let mut line = Vec::new();
+    for c in from.line_to_iter(to).filter(|&c| c != from) {
+        line.push(c);
+        if stops(c) {
+            break;
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use hex2d::Angle;
+
+    use crate::domain::common::HexDirection;
+
+    use super::*;
+
+    #[test]
+    fn attack_side_depends_on_defender_facing() {
+        let origin = Coordinate::new(0, 0);
+
+        let inputs = [
+            (Angle::Forward, AttackSide::Front),
+            (Angle::Left, AttackSide::Front),
+            (Angle::Right, AttackSide::Front),
+            (Angle::LeftBack, AttackSide::Flank),
+            (Angle::RightBack, AttackSide::Flank),
+            (Angle::Back, AttackSide::Rear),
+        ];
+
+        for facing in HexDirection::all() {
+            let defender = Position::new(origin, *facing);
+
+            for (angle, side) in inputs {
+                let attacker = origin + (*facing + angle);
+                assert_eq!(
+                    AttackSide::of(defender, attacker),
+                    side,
+                    "{facing:?} {angle:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sneak_attacks_double_damage() {
+        assert_eq!(attack_damage(2, AttackSide::Front, false), 2);
+        assert_eq!(attack_damage(2, AttackSide::Flank, false), 3);
+        assert_eq!(attack_damage(2, AttackSide::Rear, false), 4);
+        assert_eq!(attack_damage(2, AttackSide::Front, true), 4);
+        assert_eq!(attack_damage(200, AttackSide::Rear, true), u8::MAX);
+        assert_eq!(attack_damage(u8::MAX, AttackSide::Flank, false), u8::MAX);
+        assert_eq!(attack_damage(2, AttackSide::Rear, true), 8);
+    }
+
+    #[test]
+    fn line_of_fire_stops_at_first_obstacle() {
+        let from = Coordinate::new(0, 0);
+        let to = Coordinate::new(0, 4);
+        let wall = Coordinate::new(0, 2);
+
+        let clear = line_of_fire(from, to, |_| false);
+        assert_eq!(clear.len(), 4);
+        assert_eq!(clear.last(), Some(&to));
+
+        let blocked = line_of_fire(from, to, |c| c == wall);
+        assert_eq!(blocked, vec![Coordinate::new(0, 1), wall]);
+    }
+}
@@ -1,18 +1,19 @@
 use bevy::prelude::*;
 
 use hex2d::*;
+use serde::{Deserialize, Serialize};
 
 pub const HEX_SPACING: Spacing = Spacing::FlatTop(40.0);
 
 pub type HexDirection = hex2d::Direction;
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Actor {
     pub actions_per_turn: u8,
     pub actions_remaining: u8,
 }
 
-#[derive(Component)]
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Facing(pub HexDirection);
 
 impl Facing {
@@ -32,7 +33,7 @@ impl Default for Facing {
     }
 }
 
-#[derive(Component, PartialEq, Eq, Clone, Hash, Debug)]
+#[derive(Component, PartialEq, Eq, Clone, Hash, Debug, Serialize, Deserialize)]
 pub struct HexPos(pub Coordinate);
 
 impl HexPos {
@@ -45,6 +46,10 @@ impl HexPos {
         // the y-axis points upward, so invert it
         Vec3::new(x, -y, 0.0)
     }
+
+    pub fn from_translation(translation: Vec3, spacing: Spacing) -> HexPos {
+        HexPos(Coordinate::from_pixel(translation.x, -translation.y, spacing))
+    }
 }
 
 impl Default for HexPos {
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{
+        effects::set_health::SetHealthEffect,
+        health::{mitigate, Armor, DamageType, Health, Resistances},
+    },
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DamageEffect {
+    #[serde(with = "entity_serde")]
+    pub target: Entity,
+    pub amount: u8,
+    pub damage_type: DamageType,
+}
+
+impl DamageEffect {
+    pub fn new(target: Entity, amount: u8, damage_type: DamageType) -> DamageEffect {
+        DamageEffect {
+            target,
+            amount,
+            damage_type,
+        }
+    }
+}
+
+impl Effect for DamageEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let health = world.get::<Health>(self.target)?;
+        Some(SetHealthEffect::new(self.target, health.current).into())
+    }
+}
+
+pub fn handler(
+    In(effect): In<DamageEffect>,
+    mut targets: Query<(&mut Health, Option<&Armor>, Option<&Resistances>)>,
+) {
+    if let Ok((mut health, armor, resistances)) = targets.get_mut(effect.target) {
+        let damage = mitigate(effect.amount, effect.damage_type, armor, resistances);
+        health.current = health.current.saturating_sub(damage);
+    }
+}
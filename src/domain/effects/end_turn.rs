@@ -1,11 +1,12 @@
-use crate::turn_engine::effects::Effect;
+use crate::turn_engine::{effects::Effect, entity_serde};
 
 use crate::domain::common::*;
 use crate::domain::turn_queue::TurnQueue;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct EndTurnEffect(Entity);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndTurnEffect(#[serde(with = "entity_serde")] Entity);
 
 impl EndTurnEffect {
     pub fn new(entity: Entity) -> EndTurnEffect {
@@ -1,11 +1,15 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::domain::common::Actor;
+use crate::domain::{common::Actor, effects::energy_refund::EnergyRefundEffect};
 
-use crate::turn_engine::effects::Effect;
+use crate::turn_engine::{
+    effects::{AnyEffect, Effect},
+    entity_serde,
+};
 
-#[derive(Debug, Clone)]
-pub struct EnergyCostEffect(Entity, u8);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyCostEffect(#[serde(with = "entity_serde")] Entity, u8);
 
 impl EnergyCostEffect {
     pub fn new(entity: Entity, cost: u8) -> EnergyCostEffect {
@@ -13,7 +17,13 @@ impl EnergyCostEffect {
     }
 }
 
-impl Effect for EnergyCostEffect {}
+impl Effect for EnergyCostEffect {
+    // only what was actually deducted is given back, as the cost is capped at the energy remaining
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let actor = world.get::<Actor>(self.0)?;
+        Some(EnergyRefundEffect::new(self.0, self.1.min(actor.actions_remaining)).into())
+    }
+}
 
 pub fn handler(
     In(EnergyCostEffect(entity, cost)): In<EnergyCostEffect>,
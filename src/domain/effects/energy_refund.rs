@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{common::Actor, effects::energy_cost::EnergyCostEffect};
+
+use crate::turn_engine::{
+    effects::{AnyEffect, Effect},
+    entity_serde,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyRefundEffect(#[serde(with = "entity_serde")] Entity, u8);
+
+impl EnergyRefundEffect {
+    pub fn new(entity: Entity, amount: u8) -> EnergyRefundEffect {
+        EnergyRefundEffect(entity, amount)
+    }
+}
+
+impl Effect for EnergyRefundEffect {
+    fn inverse(&self, _world: &World) -> Option<AnyEffect> {
+        Some(EnergyCostEffect::new(self.0, self.1).into())
+    }
+}
+
+pub fn handler(
+    In(EnergyRefundEffect(entity, amount)): In<EnergyRefundEffect>,
+    mut actors: Query<&mut Actor>,
+) {
+    if let Ok(mut actor) = actors.get_mut(entity) {
+        actor.actions_remaining = actor.actions_remaining.saturating_add(amount);
+    }
+}
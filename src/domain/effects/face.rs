@@ -1,11 +1,15 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::common::{Facing, HexDirection};
 
-use crate::turn_engine::effects::Effect;
+use crate::turn_engine::{
+    effects::{AnyEffect, Effect},
+    entity_serde,
+};
 
-#[derive(Debug, Clone)]
-pub struct FaceEffect(pub Entity, pub HexDirection);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceEffect(#[serde(with = "entity_serde")] pub Entity, pub HexDirection);
 
 impl FaceEffect {
     pub fn new(entity: Entity, face: HexDirection) -> FaceEffect {
@@ -13,7 +17,12 @@ impl FaceEffect {
     }
 }
 
-impl Effect for FaceEffect {}
+impl Effect for FaceEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let facing = world.get::<Facing>(self.0)?;
+        Some(FaceEffect(self.0, facing.0).into())
+    }
+}
 
 pub fn handler(In(FaceEffect(entity, face)): In<FaceEffect>, mut facings: Query<&mut Facing>) {
     if let Ok(mut facing) = facings.get_mut(entity) {
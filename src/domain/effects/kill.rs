@@ -1,9 +1,17 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::turn_engine::effects::Effect;
+use crate::{
+    domain::{effects::respawn::RespawnEffect, turn_queue::TurnQueue},
+    save::SavedActor,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
 
-#[derive(Debug, Clone)]
-pub struct KillEffect(Entity);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillEffect(#[serde(with = "entity_serde")] Entity);
 
 impl KillEffect {
     pub fn new(entity: Entity) -> KillEffect {
@@ -11,7 +19,13 @@ impl KillEffect {
     }
 }
 
-impl Effect for KillEffect {}
+impl Effect for KillEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let saved = SavedActor::capture(world, self.0)?;
+        let turn_index = world.get_resource::<TurnQueue>()?.position(self.0);
+        Some(RespawnEffect::new(self.0, saved, turn_index).into())
+    }
+}
 
 pub fn handler(In(KillEffect(entity)): In<KillEffect>, mut commands: Commands) {
     commands.entity(entity).despawn_recursive();
@@ -1,25 +1,37 @@
 use bevy::prelude::*;
 
-use crate::turn_engine::TurnSystems;
+use crate::{domain::hearing::Noise, turn_engine::TurnSystems};
 
+pub mod damage;
 pub mod end_turn;
 pub mod energy_cost;
+pub mod energy_refund;
 pub mod face;
 pub mod kill;
 pub mod move_entity;
+pub mod noise;
+pub mod respawn;
+pub mod set_ammo;
+pub mod set_health;
 
 pub struct DomainEffectsPlugin;
 
 impl Plugin for DomainEffectsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup);
+        app.add_event::<Noise>().add_startup_system(setup);
     }
 }
 
 fn setup(mut systems: ResMut<TurnSystems>) {
+    systems.register_effect_handler(damage::handler);
     systems.register_effect_handler(end_turn::handler);
     systems.register_effect_handler(energy_cost::handler);
+    systems.register_effect_handler(energy_refund::handler);
     systems.register_effect_handler(face::handler);
     systems.register_effect_handler(kill::handler);
     systems.register_effect_handler(move_entity::handler);
+    systems.register_effect_handler(noise::handler);
+    systems.register_effect_handler(respawn::handler);
+    systems.register_effect_handler(set_ammo::handler);
+    systems.register_effect_handler(set_health::handler);
 }
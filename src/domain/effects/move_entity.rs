@@ -1,10 +1,17 @@
 use bevy::prelude::*;
 use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
 
-use crate::{domain::common::HexPos, turn_engine::effects::Effect};
+use crate::{
+    domain::common::HexPos,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
 
-#[derive(Debug, Clone)]
-pub struct MoveEffect(pub Entity, pub Coordinate);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveEffect(#[serde(with = "entity_serde")] pub Entity, pub Coordinate);
 
 impl MoveEffect {
     pub fn new(entity: Entity, to: Coordinate) -> MoveEffect {
@@ -12,7 +19,12 @@ impl MoveEffect {
     }
 }
 
-impl Effect for MoveEffect {}
+impl Effect for MoveEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let from = world.get::<HexPos>(self.0)?;
+        Some(MoveEffect(self.0, from.0).into())
+    }
+}
 
 pub fn handler(In(MoveEffect(entity, to)): In<MoveEffect>, mut positions: Query<&mut HexPos>) {
     if let Ok(mut pos) = positions.get_mut(entity) {
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::hearing::Noise,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseEffect(#[serde(with = "entity_serde")] Entity, Coordinate, u8);
+
+impl NoiseEffect {
+    pub fn new(entity: Entity, source: Coordinate, loudness: u8) -> NoiseEffect {
+        NoiseEffect(entity, source, loudness)
+    }
+}
+
+impl Effect for NoiseEffect {
+    // a noise cannot be unheard, so undoing one makes no sound of its own
+    fn inverse(&self, _world: &World) -> Option<AnyEffect> {
+        Some(NoiseEffect(self.0, self.1, 0).into())
+    }
+}
+
+pub fn handler(
+    In(NoiseEffect(entity, source, loudness)): In<NoiseEffect>,
+    mut noises: EventWriter<Noise>,
+) {
+    if loudness > 0 {
+        noises.send(Noise {
+            entity,
+            source,
+            loudness,
+        });
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::{effects::kill::KillEffect, turn_queue::TurnQueue},
+    save::SavedActor,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
+
+// brings a killed actor back as the same entity, in the same place in the turn order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RespawnEffect {
+    #[serde(with = "entity_serde")]
+    entity: Entity,
+    actor: SavedActor,
+    turn_index: Option<usize>,
+}
+
+impl RespawnEffect {
+    pub fn new(entity: Entity, actor: SavedActor, turn_index: Option<usize>) -> RespawnEffect {
+        RespawnEffect {
+            entity,
+            actor,
+            turn_index,
+        }
+    }
+}
+
+impl Effect for RespawnEffect {
+    fn inverse(&self, _world: &World) -> Option<AnyEffect> {
+        Some(KillEffect::new(self.entity).into())
+    }
+}
+
+pub fn handler(
+    In(effect): In<RespawnEffect>,
+    mut commands: Commands,
+    mut turn_queue: ResMut<TurnQueue>,
+) {
+    effect
+        .actor
+        .insert_into(&mut commands.get_or_spawn(effect.entity), |id| {
+            Some(Entity::from_bits(id))
+        });
+
+    if let Some(index) = effect.turn_index {
+        turn_queue.insert(index, effect.entity);
+    }
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::combat::Ammo,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetAmmoEffect(#[serde(with = "entity_serde")] Entity, u8);
+
+impl SetAmmoEffect {
+    pub fn new(entity: Entity, current: u8) -> SetAmmoEffect {
+        SetAmmoEffect(entity, current)
+    }
+}
+
+impl Effect for SetAmmoEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let ammo = world.get::<Ammo>(self.0)?;
+        Some(SetAmmoEffect(self.0, ammo.current).into())
+    }
+}
+
+pub fn handler(In(SetAmmoEffect(entity, current)): In<SetAmmoEffect>, mut ammo: Query<&mut Ammo>) {
+    if let Ok(mut ammo) = ammo.get_mut(entity) {
+        ammo.current = current.min(ammo.max);
+    }
+}
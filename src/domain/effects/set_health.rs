@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    domain::health::Health,
+    turn_engine::{
+        effects::{AnyEffect, Effect},
+        entity_serde,
+    },
+};
+
+// restores health to an exact amount, e.g. when undoing damage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetHealthEffect(#[serde(with = "entity_serde")] Entity, u8);
+
+impl SetHealthEffect {
+    pub fn new(entity: Entity, current: u8) -> SetHealthEffect {
+        SetHealthEffect(entity, current)
+    }
+}
+
+impl Effect for SetHealthEffect {
+    fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        let health = world.get::<Health>(self.0)?;
+        Some(SetHealthEffect(self.0, health.current).into())
+    }
+}
+
+pub fn handler(
+    In(SetHealthEffect(entity, current)): In<SetHealthEffect>,
+    mut healths: Query<&mut Health>,
+) {
+    if let Ok(mut health) = healths.get_mut(entity) {
+        health.current = current.min(health.max);
+    }
+}
@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hex2d::{Coordinate, Direction, Position, Spin};
+
+use crate::map::{MapTiles, MapVersion};
+
+use super::{
+    common::{Facing, HexPos},
+    light::{LightMap, LightMapUpdate},
+    los::{LosRule, Sight},
+    vision::Vision,
+};
+
+pub struct FieldOfViewPlugin;
+
+impl Plugin for FieldOfViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            update_field_of_view
+                .label(FieldOfViewUpdate)
+                .after(LightMapUpdate),
+        );
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub struct FieldOfViewUpdate;
+
+// every hex an entity with `Vision` can currently see and how well
+//
+// what it has a line of sight to is only worked out again when it moves or turns, or the map
+// changes; when only the lighting changes, that is narrowed down afresh to what is lit enough
+#[derive(Component, Clone, Debug, Default)]
+pub struct FieldOfView {
+    key: Option<(Position, MapVersion)>,
+    in_sight: HashMap<Coordinate, Sight>,
+    visible: HashMap<Coordinate, Sight>,
+}
+
+impl FieldOfView {
+    pub fn compute(
+        vision: &Vision,
+        pos: Position,
+        walls: &HashSet<Coordinate>,
+        light: &LightMap,
+    ) -> HashMap<Coordinate, Sight> {
+        let in_sight = FieldOfView::line_of_sight(vision, pos, walls);
+        FieldOfView::narrow(vision, pos, &in_sight, light)
+    }
+
+    // the hexes in reach with nothing opaque in the way, regardless of how they are lit
+    fn line_of_sight(
+        vision: &Vision,
+        pos: Position,
+        walls: &HashSet<Coordinate>,
+    ) -> HashMap<Coordinate, Sight> {
+        let radius = vision.vision.reach();
+        let is_opaque = |x: Coordinate| walls.contains(&x);
+
+        match vision.los {
+            LosRule::Shadowcast => shadowcast(pos.coord, radius, is_opaque)
+                .into_iter()
+                .map(|c| (c, Sight::Clear))
+                .collect(),
+            rule => pos
+                .coord
+                .range_iter(radius)
+                .map(|c| (c, rule.sight(pos.coord, c, is_opaque)))
+                .filter(|(_, sight)| sight.is_visible())
+                .collect(),
+        }
+    }
+
+    // what the rest of `vision` makes of the hexes in sight; hexes seen regardless of what is in
+    // the way are seen clearly
+    fn narrow(
+        vision: &Vision,
+        pos: Position,
+        in_sight: &HashMap<Coordinate, Sight>,
+        light: &LightMap,
+    ) -> HashMap<Coordinate, Sight> {
+        pos.coord
+            .range_iter(vision.vision.reach())
+            .filter(|&c| {
+                vision.can_see_relative(pos, c, |x| in_sight.contains_key(&x), |x| light.is_lit(x))
+            })
+            .map(|c| (c, in_sight.get(&c).copied().unwrap_or(Sight::Clear)))
+            .collect()
+    }
+
+    pub fn can_see(&self, c: Coordinate) -> bool {
+        self.visible.contains_key(&c)
+    }
+
+    pub fn sight(&self, c: Coordinate) -> Sight {
+        self.visible.get(&c).copied().unwrap_or(Sight::Blocked)
+    }
+}
+
+// a fraction of a full turn around the origin
+#[derive(Clone, Copy, Debug)]
+struct Turn {
+    num: i64,
+    den: i64,
+}
+
+impl Turn {
+    fn new(num: i64, den: i64) -> Turn {
+        Turn { num, den }
+    }
+
+    fn plus(self, whole: i64) -> Turn {
+        Turn::new(self.num + whole * self.den, self.den)
+    }
+
+    fn cmp(self, other: Turn) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+// every hex within `radius` of `origin` which is not hidden behind an opaque one
+//
+// works outwards a ring at a time; each hex covers an equal share of its ring, and opaque hexes
+// cast that share as a shadow over every ring beyond. a hex is seen unless its centre lies inside
+// a shadow, so hexes exactly on the edge of one are still seen
+pub fn shadowcast(
+    origin: Coordinate,
+    radius: i32,
+    is_opaque: impl Fn(Coordinate) -> bool,
+) -> HashSet<Coordinate> {
+    let mut visible = HashSet::default();
+    visible.insert(origin);
+
+    let mut shadows: Vec<(Turn, Turn)> = Vec::new();
+    for r in 1..=radius {
+        // measured in half hexes, so that both centres and edges are whole numbers
+        let den = 12 * r as i64;
+        let mut cast = Vec::new();
+
+        for (i, c) in origin.ring_iter(r, Spin::CW(Direction::YZ)).enumerate() {
+            let centre = Turn::new(2 * i as i64, den);
+            if in_shadow(&shadows, centre) {
+                continue;
+            }
+
+            visible.insert(c);
+            if is_opaque(c) {
+                cast.push((
+                    Turn::new(centre.num - 1, den),
+                    Turn::new(centre.num + 1, den),
+                ));
+            }
+        }
+
+        shadows.extend(cast);
+    }
+
+    visible
+}
+
+// shadows may straddle the start of the ring, so the point is also tried a full turn either way
+fn in_shadow(shadows: &[(Turn, Turn)], point: Turn) -> bool {
+    shadows.iter().any(|&(start, end)| {
+        [point.plus(-1), point, point.plus(1)]
+            .iter()
+            .any(|&p| start.cmp(p) == Ordering::Less && p.cmp(end) == Ordering::Less)
+    })
+}
+
+pub fn update_field_of_view(
+    mut commands: Commands,
+    mut seers: Query<(
+        Entity,
+        &HexPos,
+        &Facing,
+        &Vision,
+        ChangeTrackers<Vision>,
+        Option<&mut FieldOfView>,
+    )>,
+    version: Res<MapVersion>,
+    light: Res<LightMap>,
+    map: MapTiles,
+) {
+    let mut walls = None;
+
+    for (e, &HexPos(coord), &Facing(dir), vision, vision_changes, mut fov) in seers.iter_mut() {
+        let pos = Position::new(coord, dir);
+        let key = Some((pos, *version));
+
+        let moved = vision_changes.is_changed() || fov.as_ref().map_or(true, |fov| fov.key != key);
+        if !moved && !light.is_changed() {
+            continue;
+        }
+
+        let mut added = FieldOfView::default();
+        let current = match &mut fov {
+            Some(fov) => &mut **fov,
+            None => &mut added,
+        };
+
+        if moved {
+            let walls = walls.get_or_insert_with(|| map.get_opaque());
+            current.key = key;
+            current.in_sight = FieldOfView::line_of_sight(vision, pos, walls);
+        }
+        current.visible = FieldOfView::narrow(vision, pos, &current.in_sight, &light);
+
+        if fov.is_none() {
+            commands.entity(e).insert(added);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{light::LightSource, vision::VisionType};
+
+    use super::*;
+
+    fn walls(coords: &[Coordinate]) -> HashSet<Coordinate> {
+        coords.iter().copied().collect()
+    }
+
+    #[test]
+    fn walls_are_seen_but_hide_what_is_behind_them() {
+        let origin = Coordinate::new(0, 0);
+        let wall = origin + Direction::YZ;
+        let seen = shadowcast(origin, 4, |x| x == wall);
+
+        assert!(seen.contains(&origin));
+        assert!(seen.contains(&wall));
+        assert!(!seen.contains(&(wall + Direction::YZ)));
+        assert!(!seen.contains(&(wall + Direction::YZ + Direction::YZ)));
+        // the view is not blocked to either side of the wall
+        assert!(seen.contains(&(wall + Direction::XY)));
+        assert!(seen.contains(&(origin + Direction::ZY)));
+    }
+
+    #[test]
+    fn open_ground_is_seen_out_to_the_radius() {
+        let origin = Coordinate::new(1, -2);
+        let seen = shadowcast(origin, 3, |_| false);
+
+        assert_eq!(seen.len(), origin.range_iter(3).count());
+        assert!(origin.range_iter(3).all(|c| seen.contains(&c)));
+    }
+
+    #[test]
+    fn shadows_wrap_around_the_start_of_the_ring() {
+        let origin = Coordinate::new(0, 0);
+        let first = |r| origin.ring_iter(r, Spin::CW(Direction::YZ)).next().unwrap();
+        let last = |r| origin.ring_iter(r, Spin::CW(Direction::YZ)).last().unwrap();
+        let seen = shadowcast(origin, 3, |x| x == first(1));
+
+        assert!(!seen.contains(&first(3)));
+        assert!(!seen.contains(&last(3)));
+        assert!(seen.contains(&last(1)));
+    }
+
+    #[test]
+    fn fields_of_view_combine_with_the_vision_type() {
+        let pos = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let wall = pos.coord + Direction::YZ;
+        let vision = Vision::new(VisionType::Radial(3).and(VisionType::Obstructable));
+        let fov = FieldOfView::compute(&vision, pos, &walls(&[wall]), &LightMap::default());
+
+        assert!(fov.contains_key(&wall));
+        assert!(!fov.contains_key(&(wall + Direction::YZ)));
+        let away = |n| (0..n).fold(pos.coord, |c, _| c + Direction::ZY);
+        assert!(fov.contains_key(&away(3)));
+        assert!(!fov.contains_key(&away(4)));
+    }
+
+    #[test]
+    fn players_in_the_dark_are_only_seen_up_close() {
+        let pos = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let player = (0..3).fold(pos.coord, |c, _| c + Direction::YZ);
+        let vision = Vision::new(
+            VisionType::Radial(5)
+                .and(VisionType::Obstructable)
+                .and(VisionType::Lit(1)),
+        );
+        let lit = LightMap::from_sources([(player, LightSource { radius: 1 })], &walls(&[]));
+
+        let dark = FieldOfView::compute(&vision, pos, &walls(&[]), &LightMap::default());
+        assert!(!dark.contains_key(&player));
+        assert!(dark.contains_key(&(pos.coord + Direction::YZ)));
+
+        let lit = FieldOfView::compute(&vision, pos, &walls(&[]), &lit);
+        assert!(lit.contains_key(&player));
+    }
+}
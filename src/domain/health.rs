@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Health {
+    pub current: u8,
+    pub max: u8,
+}
+
+impl Health {
+    pub fn new(max: u8) -> Health {
+        Health { current: max, max }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Blunt,
+}
+
+// taken off every hit, after resistances
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Armor(pub u8);
+
+// damage of these types is halved
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Resistances(pub Vec<DamageType>);
+
+// how much health a hit actually takes away from its target
+pub fn mitigate(
+    amount: u8,
+    damage_type: DamageType,
+    armor: Option<&Armor>,
+    resistances: Option<&Resistances>,
+) -> u8 {
+    let resisted = match resistances {
+        Some(Resistances(types)) if types.contains(&damage_type) => amount / 2,
+        _ => amount,
+    };
+    resisted.saturating_sub(armor.map_or(0, |a| a.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resistance_applies_before_armor() {
+        let armor = Armor(1);
+        let resist = Resistances(vec![DamageType::Blunt]);
+
+        assert_eq!(mitigate(5, DamageType::Slashing, None, None), 5);
+        assert_eq!(mitigate(5, DamageType::Slashing, Some(&armor), None), 4);
+        assert_eq!(mitigate(5, DamageType::Blunt, None, Some(&resist)), 2);
+        assert_eq!(
+            mitigate(5, DamageType::Blunt, Some(&armor), Some(&resist)),
+            1
+        );
+        assert_eq!(mitigate(1, DamageType::Slashing, Some(&Armor(3)), None), 0);
+    }
+}
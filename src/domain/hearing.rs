@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::map::Terrain;
+
+// lets an actor hear noises; `acuity` is how many steps further than others it can hear them
+#[derive(Component, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Hearing {
+    pub acuity: u8,
+}
+
+impl Hearing {
+    pub fn can_hear(&self, noise: &Noise, steps: i32) -> bool {
+        steps <= noise.loudness as i32 + self.acuity as i32
+    }
+}
+
+// sent whenever `entity` makes a noise, which can be heard up to `loudness` steps from `source`
+#[derive(Debug, Clone)]
+pub struct Noise {
+    pub entity: Entity,
+    pub source: Coordinate,
+    pub loudness: u8,
+}
+
+// the number of steps it takes for sound to reach each hex within `max` steps of `source`; sound
+// travels only over terrain which can be walked on, so walls and chasms lengthen the way it has
+// to go rather than muffling it
+pub fn propagate(
+    source: Coordinate,
+    max: i32,
+    terrain: &HashMap<Coordinate, Terrain>,
+) -> HashMap<Coordinate, i32> {
+    let mut steps = HashMap::default();
+    steps.insert(source, 0);
+    let mut to_visit = VecDeque::from([source]);
+
+    while let Some(current) = to_visit.pop_front() {
+        let next = steps[&current] + 1;
+        if next > max {
+            continue;
+        }
+
+        for neighbor in current.neighbors() {
+            let open = terrain
+                .get(&neighbor)
+                .map_or(false, |t| t.properties().is_walkable());
+            if open && !steps.contains_key(&neighbor) {
+                steps.insert(neighbor, next);
+                to_visit.push_back(neighbor);
+            }
+        }
+    }
+
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use hex2d::{Direction, Spin};
+
+    use crate::map::open_floor;
+
+    use super::*;
+
+    #[test]
+    fn sound_goes_around_walls() {
+        let mut terrain = open_floor(3);
+        let source = Coordinate::new(0, 0);
+        let behind = source + Direction::YZ + Direction::YZ;
+
+        assert_eq!(propagate(source, 3, &terrain).get(&behind), Some(&2));
+
+        terrain.insert(source + Direction::YZ, Terrain::Wall);
+        let steps = propagate(source, 3, &terrain);
+        assert_eq!(steps.get(&behind), Some(&3));
+        assert_eq!(steps.get(&(source + Direction::YZ)), None);
+
+        assert_eq!(propagate(source, 2, &terrain).get(&behind), None);
+    }
+
+    #[test]
+    fn sound_does_not_cross_chasms() {
+        let mut terrain = open_floor(3);
+        let source = Coordinate::new(0, 0);
+        for c in source.ring_iter(1, Spin::CW(Direction::YZ)) {
+            terrain.insert(c, Terrain::Chasm);
+        }
+
+        let listener = source + Direction::YZ + Direction::YZ;
+        assert_eq!(propagate(source, 3, &terrain).get(&listener), None);
+    }
+}
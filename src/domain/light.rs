@@ -0,0 +1,124 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::map::{MapTiles, MapVersion};
+
+use super::{common::HexPos, fov::shadowcast};
+
+pub struct LightPlugin;
+
+impl Plugin for LightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightMap>().add_system_to_stage(
+            CoreStage::PostUpdate,
+            update_light_map.label(LightMapUpdate),
+        );
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub struct LightMapUpdate;
+
+// lights up every hex within `radius` which it has a line of sight to, dimming with distance
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightSource {
+    pub radius: i32,
+}
+
+impl LightSource {
+    // how brightly `distance` away is lit, as a percentage
+    fn level_at(&self, distance: i32) -> u8 {
+        if distance > self.radius {
+            0
+        } else {
+            (100 * (self.radius + 1 - distance) / (self.radius + 1)) as u8
+        }
+    }
+}
+
+// how brightly lit each hex is, as a percentage; hexes which are not lit at all are left out
+#[derive(Default, Debug)]
+pub struct LightMap {
+    levels: HashMap<Coordinate, u8>,
+}
+
+impl LightMap {
+    // lights are blocked by walls just as sight is
+    pub fn from_sources(
+        sources: impl IntoIterator<Item = (Coordinate, LightSource)>,
+        walls: &HashSet<Coordinate>,
+    ) -> LightMap {
+        let mut levels = HashMap::<Coordinate, u8>::default();
+
+        for (source, light) in sources {
+            for c in shadowcast(source, light.radius, |x| walls.contains(&x)) {
+                let level = light.level_at(source.distance(c));
+                if level > 0 {
+                    let current = levels.entry(c).or_insert(0);
+                    *current = (*current).max(level);
+                }
+            }
+        }
+
+        LightMap { levels }
+    }
+
+    pub fn level(&self, c: Coordinate) -> u8 {
+        self.levels.get(&c).copied().unwrap_or(0)
+    }
+
+    pub fn is_lit(&self, c: Coordinate) -> bool {
+        self.level(c) > 0
+    }
+}
+
+fn update_light_map(
+    sources: Query<(&HexPos, &LightSource)>,
+    changed: Query<
+        (),
+        (
+            With<LightSource>,
+            Or<(Changed<HexPos>, Changed<LightSource>)>,
+        ),
+    >,
+    removed: RemovedComponents<LightSource>,
+    version: Res<MapVersion>,
+    map: MapTiles,
+    mut light: ResMut<LightMap>,
+) {
+    let stale =
+        version.is_changed() || changed.iter().next().is_some() || removed.iter().next().is_some();
+
+    if stale {
+        *light = LightMap::from_sources(
+            sources.iter().map(|(pos, source)| (pos.0, *source)),
+            &map.get_opaque(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex2d::Direction;
+
+    use super::*;
+
+    #[test]
+    fn light_fades_with_distance_and_stops_at_walls() {
+        let torch = Coordinate::new(0, 0);
+        let wall = torch + Direction::YZ;
+        let walls: HashSet<_> = [wall].into_iter().collect();
+        let away = |n| (0..n).fold(torch, |c, _| c + Direction::ZY);
+        let light = LightMap::from_sources([(torch, LightSource { radius: 3 })], &walls);
+
+        assert_eq!(light.level(torch), 100);
+        assert!(light.level(away(1)) > light.level(away(2)));
+        assert!(light.is_lit(wall));
+        assert!(!light.is_lit(wall + Direction::YZ));
+        assert!(!light.is_lit(away(4)));
+    }
+}
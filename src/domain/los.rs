@@ -0,0 +1,191 @@
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use super::fov::shadowcast;
+
+// how much of a hex can be seen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Sight {
+    Blocked,
+    // half hidden behind cover, but still visible
+    Partial,
+    Clear,
+}
+
+impl Sight {
+    pub fn is_visible(self) -> bool {
+        self != Sight::Blocked
+    }
+}
+
+// how opaque hexes between two points decide whether one can see the other
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LosRule {
+    // hexes are hidden by the shadows cast outwards from the viewer; cheap, but a viewer may be
+    // seen by someone they cannot see
+    Shadowcast,
+    // sees only what both of the lines either side of the direct line reach, so there is no
+    // seeing round corners
+    Symmetric,
+    // sees what either line reaches, with anything only one of them reaches being in partial cover
+    Permissive,
+}
+
+impl Default for LosRule {
+    fn default() -> Self {
+        LosRule::Shadowcast
+    }
+}
+
+// each line is pushed this far to one side so that it never runs exactly along the edge between two
+// hexes, where it could go either way
+const NUDGE: f64 = 1e-6;
+
+impl LosRule {
+    // how well `from` can see `to`; the hexes at either end never block the view. both line based
+    // rules are symmetric, giving the same answer when `from` and `to` are swapped
+    pub fn sight(
+        self,
+        from: Coordinate,
+        to: Coordinate,
+        is_opaque: impl Fn(Coordinate) -> bool,
+    ) -> Sight {
+        let clear = |nudge| {
+            nudged_line(from, to, nudge)
+                .filter(|&c| c != from && c != to)
+                .all(|c| !is_opaque(c))
+        };
+
+        match self {
+            LosRule::Shadowcast => {
+                if shadowcast(from, from.distance(to), &is_opaque).contains(&to) {
+                    Sight::Clear
+                } else {
+                    Sight::Blocked
+                }
+            }
+            LosRule::Symmetric => {
+                if clear(NUDGE) && clear(-NUDGE) {
+                    Sight::Clear
+                } else {
+                    Sight::Blocked
+                }
+            }
+            LosRule::Permissive => match (clear(NUDGE), clear(-NUDGE)) {
+                (true, true) => Sight::Clear,
+                (false, false) => Sight::Blocked,
+                _ => Sight::Partial,
+            },
+        }
+    }
+}
+
+// the hexes along the line from `from` to `to`, shifted by `nudge`
+//
+// each point is worked out from both ends' coordinates in the same way whichever way round they
+// are given, so a line and its reverse cover exactly the same hexes
+fn nudged_line(from: Coordinate, to: Coordinate, nudge: f64) -> impl Iterator<Item = Coordinate> {
+    let n = from.distance(to).max(1);
+    (0..=n).map(move |i| {
+        let lerp = |a: i32, b: i32| (a * (n - i) + b * i) as f64 / n as f64;
+        round(lerp(from.x, to.x) + nudge, lerp(from.y, to.y) + 2.0 * nudge)
+    })
+}
+
+// the hex containing the fractional cube coordinate (x, y, -x - y)
+fn round(x: f64, y: f64) -> Coordinate {
+    let z = -x - y;
+    let (rx, ry, rz) = (x.round(), y.round(), z.round());
+    let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+
+    if dx > dy && dx > dz {
+        Coordinate::new((-ry - rz) as i32, ry as i32)
+    } else if dy > dz {
+        Coordinate::new(rx as i32, (-rx - rz) as i32)
+    } else {
+        Coordinate::new(rx as i32, ry as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+    use hex2d::Direction;
+    use rand::Rng;
+
+    use crate::rng::GameRng;
+
+    use super::*;
+
+    const RADIUS: i32 = 6;
+
+    // a patch of ground strewn with walls, different for every seed
+    fn generated_walls(seed: u64) -> HashSet<Coordinate> {
+        let mut rng = GameRng::new(seed);
+        Coordinate::new(0, 0)
+            .range_iter(RADIUS)
+            .filter(|_| rng.gen_bool(0.3))
+            .collect()
+    }
+
+    fn every_pair() -> impl Iterator<Item = (Coordinate, Coordinate)> {
+        let origin = Coordinate::new(0, 0);
+        origin
+            .range_iter(RADIUS)
+            .flat_map(move |a| origin.range_iter(RADIUS).map(move |b| (a, b)))
+    }
+
+    #[test]
+    fn line_rules_are_symmetric() {
+        for seed in 0..10 {
+            let walls = generated_walls(seed);
+            let is_opaque = |c: Coordinate| walls.contains(&c);
+
+            for (a, b) in every_pair() {
+                for rule in [LosRule::Symmetric, LosRule::Permissive] {
+                    assert_eq!(
+                        rule.sight(a, b, is_opaque),
+                        rule.sight(b, a, is_opaque),
+                        "{:?} from {:?} to {:?} with seed {}",
+                        rule,
+                        a,
+                        b,
+                        seed
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn permissive_sees_everything_symmetric_does() {
+        for seed in 0..10 {
+            let walls = generated_walls(seed);
+            let is_opaque = |c: Coordinate| walls.contains(&c);
+
+            for (a, b) in every_pair() {
+                let strict = LosRule::Symmetric.sight(a, b, is_opaque);
+                let permissive = LosRule::Permissive.sight(a, b, is_opaque);
+                assert!(permissive >= strict, "from {:?} to {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn lines_grazing_a_corner_are_in_partial_cover() {
+        let from = Coordinate::new(0, 0);
+        // runs exactly between the two hexes either side of it
+        let to = from + Direction::YZ + Direction::XZ;
+        let left = from + Direction::YZ;
+        let right = from + Direction::XZ;
+
+        let sight =
+            |rule: LosRule, walls: &[Coordinate]| rule.sight(from, to, |c| walls.contains(&c));
+
+        assert_eq!(sight(LosRule::Permissive, &[]), Sight::Clear);
+        assert_eq!(sight(LosRule::Permissive, &[left]), Sight::Partial);
+        assert_eq!(sight(LosRule::Permissive, &[right]), Sight::Partial);
+        assert_eq!(sight(LosRule::Permissive, &[left, right]), Sight::Blocked);
+        assert_eq!(sight(LosRule::Symmetric, &[left]), Sight::Blocked);
+    }
+}
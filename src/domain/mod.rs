@@ -1,10 +1,20 @@
 use bevy::prelude::*;
 
-use self::{actions::DomainActionsPlugin, effects::DomainEffectsPlugin};
+use self::{
+    actions::DomainActionsPlugin, effects::DomainEffectsPlugin, fov::FieldOfViewPlugin,
+    light::LightPlugin, reactions::DomainReactionsPlugin,
+};
 
 pub mod actions;
+pub mod combat;
 pub mod common;
 pub mod effects;
+pub mod fov;
+pub mod health;
+pub mod hearing;
+pub mod light;
+pub mod los;
+pub mod reactions;
 pub mod turn_queue;
 pub mod vision;
 
@@ -13,6 +23,9 @@ pub struct DomainPlugin;
 impl Plugin for DomainPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(DomainActionsPlugin)
-            .add_plugin(DomainEffectsPlugin);
+            .add_plugin(DomainEffectsPlugin)
+            .add_plugin(DomainReactionsPlugin)
+            .add_plugin(LightPlugin)
+            .add_plugin(FieldOfViewPlugin);
     }
 }
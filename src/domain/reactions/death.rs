@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use crate::{
+    domain::{
+        effects::{damage::DamageEffect, kill::KillEffect},
+        health::{mitigate, Armor, Health, Resistances},
+    },
+    turn_engine::{effects::EffectQueue, reactions::Reaction},
+};
+
+// an actor dies once a hit takes its last health; actors without health die from any hit
+pub fn reaction(
+    In(damage): In<DamageEffect>,
+    targets: Query<(Option<&Health>, Option<&Armor>, Option<&Resistances>)>,
+) -> Reaction {
+    if let Ok((health, armor, resistances)) = targets.get(damage.target) {
+        let dealt = mitigate(damage.amount, damage.damage_type, armor, resistances);
+        let fatal = match health {
+            Some(health) => dealt >= health.current,
+            None => dealt > 0,
+        };
+
+        if fatal {
+            return Reaction::follow_up(EffectQueue::new(KillEffect::new(damage.target)));
+        }
+    }
+    Reaction::none()
+}
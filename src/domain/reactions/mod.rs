@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+use crate::turn_engine::TurnSystems;
+
+pub mod death;
+pub mod opportunity_attack;
+
+pub struct DomainReactionsPlugin;
+
+impl Plugin for DomainReactionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup);
+    }
+}
+
+fn setup(mut systems: ResMut<TurnSystems>) {
+    systems.register_reaction(death::reaction);
+    systems.register_reaction(opportunity_attack::reaction);
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{
+    ai::ai_vision::CanSeePlayer,
+    domain::{
+        common::{Facing, HexPos},
+        effects::{damage::DamageEffect, move_entity::MoveEffect},
+        health::DamageType,
+    },
+    turn_engine::{effects::EffectQueue, reactions::Reaction},
+    Player,
+};
+
+const OPPORTUNITY_DAMAGE: u8 = 2;
+
+// an alert enemy strikes the player as they step into the hex it is facing; the player cannot undo
+// their way out of finding that out
+pub fn reaction(
+    In(MoveEffect(mover, to)): In<MoveEffect>,
+    players: Query<(), With<Player>>,
+    guards: Query<(Entity, &HexPos, &Facing), With<CanSeePlayer>>,
+) -> Reaction {
+    if players.get(mover).is_err() {
+        return Reaction::none();
+    }
+
+    let mut effects = EffectQueue::default();
+    for (e, pos, facing) in guards.iter() {
+        if e != mover && pos.get_facing(facing.0) == to {
+            effects.push(DamageEffect::new(
+                mover,
+                OPPORTUNITY_DAMAGE,
+                DamageType::Slashing,
+            ));
+        }
+    }
+
+    if effects.is_empty() {
+        return Reaction::none();
+    }
+    Reaction::sealing(effects)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::System;
+    use hex2d::Coordinate;
+
+    use crate::domain::common::HexDirection;
+
+    use super::*;
+
+    fn reacts(world: &mut World, effect: MoveEffect) -> bool {
+        let mut system = reaction.system();
+        system.initialize(world);
+        system
+            .run(effect, world)
+            .effects
+            .find::<DamageEffect>()
+            .is_some()
+    }
+
+    #[test]
+    fn alert_guard_strikes_player_entering_its_front() {
+        let mut world = World::new();
+        let origin = Coordinate::new(0, 0);
+        let front = origin + HexDirection::YZ;
+
+        let player = world.spawn().insert_bundle((Player, HexPos(origin))).id();
+        let guard = world
+            .spawn()
+            .insert_bundle((HexPos(front + HexDirection::YZ), Facing(-HexDirection::YZ)))
+            .id();
+
+        assert!(!reacts(&mut world, MoveEffect::new(player, front)));
+
+        world.entity_mut(guard).insert(CanSeePlayer);
+        assert!(reacts(&mut world, MoveEffect::new(player, front)));
+        assert!(!reacts(&mut world, MoveEffect::new(guard, front)));
+    }
+}
@@ -8,6 +8,7 @@ use bevy::prelude::*;
 #[derive(Default)]
 pub struct TurnQueue {
     queue: VecDeque<Entity>,
+    turns_elapsed: u32,
 }
 
 impl TurnQueue {
@@ -22,9 +23,14 @@ impl TurnQueue {
     pub fn cycle(&mut self) {
         if let Some(entity) = self.queue.pop_front() {
             self.queue.push_back(entity);
+            self.turns_elapsed += 1;
         }
     }
 
+    pub fn turns_elapsed(&self) -> u32 {
+        self.turns_elapsed
+    }
+
     pub fn remove(&mut self, entity: Entity) {
         if let Some(idx) = self.queue.iter().position(|&e| e == entity) {
             self.queue.remove(idx);
@@ -34,6 +40,24 @@ impl TurnQueue {
     pub fn enqueue(&mut self, entity: Entity) {
         self.queue.push_back(entity);
     }
+
+    pub fn position(&self, entity: Entity) -> Option<usize> {
+        self.queue.iter().position(|&e| e == entity)
+    }
+
+    pub fn insert(&mut self, index: usize, entity: Entity) {
+        self.queue.insert(index.min(self.queue.len()), entity);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.queue.iter()
+    }
+
+    // replaces the queue with a previously saved order
+    pub fn restore(&mut self, order: impl IntoIterator<Item = Entity>, turns_elapsed: u32) {
+        self.queue = order.into_iter().collect();
+        self.turns_elapsed = turns_elapsed;
+    }
 }
 
 fn remove_dead_from_queue(dead: RemovedComponents<Actor>, mut turn_queue: ResMut<TurnQueue>) {
@@ -1,10 +1,16 @@
 use bevy::prelude::*;
 use hex2d::{Coordinate, Position};
+use serde::{Deserialize, Serialize};
 
 use crate::maths::{radians_from_yz, Radians};
 
-use super::common::HexDirection;
+use super::{common::HexDirection, los::LosRule};
 
+// how far a vision with no `Radial` limit on it, such as a bare `Obstructable`, can see
+pub const UNBOUNDED_RANGE: i32 = 12;
+
+// visions which are not limited by a `Radial` are cut off at `UNBOUNDED_RANGE`
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum VisionType {
     Radial(i32),
     Intersection(Box<VisionType>, Box<VisionType>),
@@ -12,21 +18,30 @@ pub enum VisionType {
     Negative(Box<VisionType>),
     Conical(Radians),
     Obstructable,
+    // sees anything within the radius, and beyond that only what is lit
+    Lit(i32),
 }
 
 impl VisionType {
-    pub fn can_see<F: Fn(Coordinate) -> bool>(&self, point: Coordinate, is_obstacle: &F) -> bool {
+    // `in_sight` tells whether a point has a clear line of sight to it; `FieldOfView` works this
+    // out for every point at once
+    pub fn can_see<F: Fn(Coordinate) -> bool, L: Fn(Coordinate) -> bool>(
+        &self,
+        point: Coordinate,
+        in_sight: &F,
+        is_lit: &L,
+    ) -> bool {
         match self {
             VisionType::Radial(radius) => {
                 point.x.abs().max(point.y.abs()).max(point.z().abs()) <= *radius
             }
             VisionType::Intersection(a, b) => {
-                a.can_see(point, is_obstacle) && b.can_see(point, is_obstacle)
+                a.can_see(point, in_sight, is_lit) && b.can_see(point, in_sight, is_lit)
             }
             VisionType::Union(a, b) => {
-                a.can_see(point, is_obstacle) || b.can_see(point, is_obstacle)
+                a.can_see(point, in_sight, is_lit) || b.can_see(point, in_sight, is_lit)
             }
-            VisionType::Negative(a) => !a.can_see(point, is_obstacle),
+            VisionType::Negative(a) => !a.can_see(point, in_sight, is_lit),
             VisionType::Conical(view_width) => {
                 if point == Coordinate::new(0, 0) {
                     return true;
@@ -36,23 +51,31 @@ impl VisionType {
 
                 angle_to_point <= *view_width
             }
-            VisionType::Obstructable => {
-                let line = Coordinate::new(0, 0).line_to_iter(point);
-
-                for x in line {
-                    if point == x {
-                        return true;
-                    }
-                    if is_obstacle(x) {
-                        return false;
-                    }
-                }
-
-                true
+            VisionType::Obstructable => in_sight(point),
+            VisionType::Lit(dark_radius) => {
+                VisionType::Radial(*dark_radius).can_see(point, in_sight, is_lit) || is_lit(point)
             }
         }
     }
 
+    // how far this can possibly see, if there is a limit
+    pub fn range(&self) -> Option<i32> {
+        match self {
+            VisionType::Radial(radius) => Some(*radius),
+            VisionType::Intersection(a, b) => match (a.range(), b.range()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+            VisionType::Union(a, b) => Some(a.range()?.max(b.range()?)),
+            _ => None,
+        }
+    }
+
+    // how far out this is worked out to, limit or no
+    pub fn reach(&self) -> i32 {
+        self.range().unwrap_or(UNBOUNDED_RANGE)
+    }
+
     pub fn and(self, other: VisionType) -> VisionType {
         VisionType::Intersection(Box::new(self), Box::new(other))
     }
@@ -62,36 +85,47 @@ impl VisionType {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct Vision {
     pub vision: VisionType,
+    // decides what `VisionType::Obstructable` can see
+    #[serde(default)]
+    pub los: LosRule,
 }
 
 impl Vision {
     pub fn new(vision: VisionType) -> Vision {
-        Vision { vision }
+        Vision {
+            vision,
+            los: LosRule::default(),
+        }
     }
 
     pub fn new_radial(radius: i32) -> Vision {
-        Vision {
-            vision: VisionType::Radial(radius),
-        }
+        Vision::new(VisionType::Radial(radius))
+    }
+
+    pub fn with_los(self, los: LosRule) -> Vision {
+        Vision { los, ..self }
     }
 
-    pub fn can_see_relative<F: Fn(Coordinate) -> bool>(
+    pub fn can_see_relative<F: Fn(Coordinate) -> bool, L: Fn(Coordinate) -> bool>(
         &self,
         pos: Position,
         point: Coordinate,
-        is_obstacle: F,
+        in_sight: F,
+        is_lit: L,
     ) -> bool {
         let relative_point = translate_to_relative(pos, point);
-        self.vision.can_see(relative_point, &|x| {
-            is_obstacle(translate_from_relative(pos, x))
-        })
+        self.vision.can_see(
+            relative_point,
+            &|x| in_sight(translate_from_relative(pos, x)),
+            &|x| is_lit(translate_from_relative(pos, x)),
+        )
     }
 }
 
-fn translate_to_relative(pos: Position, point: Coordinate) -> Coordinate {
+pub(crate) fn translate_to_relative(pos: Position, point: Coordinate) -> Coordinate {
     (point - pos.coord).rotate_around_zero(HexDirection::YZ - pos.dir)
 }
 
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    ai::AIBehaviour, domain::turn_queue::TurnQueue, intention::PlayerControlled, Player, RunParams,
+    SimulationPlugins,
+};
+
+// how many frames may pass without a turn ending before the simulation is considered stuck
+const STALL_LIMIT: u32 = 1000;
+
+// hands the player over to the AI so that a simulation can run without input
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(autopilot_player);
+    }
+}
+
+fn autopilot_player(
+    mut commands: Commands,
+    players: Query<Entity, (With<Player>, Without<AIBehaviour>)>,
+) {
+    for entity in players.iter() {
+        commands
+            .entity(entity)
+            .remove::<PlayerControlled>()
+            .insert(AIBehaviour::wanderer());
+    }
+}
+
+pub fn headless_app(params: &RunParams) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(SimulationPlugins)
+        .add_plugin(HeadlessPlugin);
+
+    params.configure(&mut app);
+
+    app
+}
+
+// runs the turn engine until `max_turns` turns have been taken or the player has died
+pub fn run_headless(params: RunParams, max_turns: u32) -> App {
+    let mut app = headless_app(&params);
+    run_turns(&mut app, max_turns);
+    app
+}
+
+pub fn run_turns(app: &mut App, max_turns: u32) {
+    // the first update runs the startup systems which spawn the scenario
+    app.update();
+
+    let mut last_turn = 0;
+    let mut stalled_frames = 0;
+
+    loop {
+        let turns = turns_elapsed(&app.world);
+        if turns >= max_turns || !player_alive(&mut app.world) {
+            break;
+        }
+
+        if turns == last_turn {
+            stalled_frames += 1;
+            if stalled_frames > STALL_LIMIT {
+                eprintln!("Simulation stalled on turn {turns}");
+                break;
+            }
+        } else {
+            last_turn = turns;
+            stalled_frames = 0;
+        }
+
+        app.update();
+    }
+}
+
+fn turns_elapsed(world: &World) -> u32 {
+    world
+        .get_resource::<TurnQueue>()
+        .map_or(0, |queue| queue.turns_elapsed())
+}
+
+fn player_alive(world: &mut World) -> bool {
+    world
+        .query_filtered::<(), With<Player>>()
+        .iter(world)
+        .next()
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::common::{Actor, Facing, HexPos},
+        Scenario,
+    };
+
+    use super::*;
+
+    fn actor_positions(world: &mut World) -> Vec<(u64, HexPos, i32)> {
+        let mut positions: Vec<_> = world
+            .query_filtered::<(Entity, &HexPos, &Facing), With<Actor>>()
+            .iter(world)
+            .map(|(e, pos, facing)| (e.to_bits(), pos.clone(), facing.0 as i32))
+            .collect();
+        positions.sort_by_key(|(e, _, _)| *e);
+        positions
+    }
+
+    #[test]
+    fn arena_simulation_runs_to_turn_limit() {
+        let mut app = run_headless(
+            RunParams {
+                scenario: Scenario::Arena,
+                seed: None,
+            },
+            50,
+        );
+
+        let turns = turns_elapsed(&app.world);
+        assert!(turns >= 50 || !player_alive(&mut app.world));
+    }
+
+    #[test]
+    fn cave_simulation_spawns_actors_without_rendering() {
+        let mut app = run_headless(
+            RunParams {
+                scenario: Scenario::Cave,
+                seed: None,
+            },
+            1,
+        );
+
+        let actors = app.world.query::<&Actor>().iter(&app.world).count();
+        assert!(actors > 0);
+    }
+
+    #[test]
+    fn same_seed_replays_same_simulation() {
+        let params = || RunParams {
+            scenario: Scenario::Cave,
+            seed: Some(1234),
+        };
+
+        let mut a = run_headless(params(), 30);
+        let mut b = run_headless(params(), 30);
+
+        assert_eq!(actor_positions(&mut a.world), actor_positions(&mut b.world));
+    }
+}
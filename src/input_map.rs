@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::intention::Intention;
+
+// a physical input which can trigger an intention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    // a short name for the input, as shown to the player
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBinding {
+    pub intention: Intention,
+    pub inputs: Vec<Binding>,
+}
+
+// which inputs produce which intentions; earlier bindings win when several are pressed at once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMap(pub Vec<InputBinding>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use self::Binding::*;
+
+        let bind = |intention, inputs: &[Binding]| InputBinding {
+            intention,
+            inputs: inputs.to_vec(),
+        };
+
+        InputMap(vec![
+            bind(
+                Intention::Step,
+                &[Key(KeyCode::Up), Gamepad(GamepadButtonType::DPadUp)],
+            ),
+            bind(
+                Intention::Backstep,
+                &[Key(KeyCode::Down), Gamepad(GamepadButtonType::DPadDown)],
+            ),
+            bind(
+                Intention::TurnLeft,
+                &[Key(KeyCode::Left), Gamepad(GamepadButtonType::DPadLeft)],
+            ),
+            bind(
+                Intention::TurnRight,
+                &[Key(KeyCode::Right), Gamepad(GamepadButtonType::DPadRight)],
+            ),
+            bind(
+                Intention::Strike,
+                &[Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            ),
+            bind(
+                Intention::Aim,
+                &[Key(KeyCode::F), Gamepad(GamepadButtonType::West)],
+            ),
+            bind(
+                Intention::EndTurn,
+                &[Key(KeyCode::E), Gamepad(GamepadButtonType::North)],
+            ),
+            bind(
+                Intention::Undo,
+                &[Key(KeyCode::Z), Gamepad(GamepadButtonType::LeftTrigger)],
+            ),
+            bind(
+                Intention::Redo,
+                &[Key(KeyCode::Y), Gamepad(GamepadButtonType::RightTrigger)],
+            ),
+        ])
+    }
+}
+
+impl InputMap {
+    pub fn inputs(&self, intention: &Intention) -> impl Iterator<Item = &Binding> {
+        let intention = intention.clone();
+        self.0
+            .iter()
+            .filter(move |b| b.intention == intention)
+            .flat_map(|b| b.inputs.iter())
+    }
+
+    // e.g. "Up / Pad DPadUp"
+    pub fn describe(&self, intention: &Intention) -> String {
+        self.inputs(intention)
+            .map(Binding::label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    pub fn just_pressed(&self, intention: &Intention, input: &PlayerInput) -> bool {
+        self.inputs(intention).any(|b| input.just_pressed(b))
+    }
+
+    // the first intention whose input was pressed this frame
+    pub fn pressed_intention(&self, input: &PlayerInput) -> Option<Intention> {
+        self.0
+            .iter()
+            .find(|b| b.inputs.iter().any(|i| input.just_pressed(i)))
+            .map(|b| b.intention.clone())
+    }
+
+    // replaces the inputs of every intention `overrides` mentions, in place so that which binding
+    // wins is unchanged; intentions this map lacks are added at the end
+    pub fn merged(mut self, overrides: InputMap) -> InputMap {
+        for o in overrides.0 {
+            match self.0.iter_mut().find(|b| b.intention == o.intention) {
+                Some(binding) => binding.inputs = o.inputs,
+                None => self.0.push(o),
+            }
+        }
+        self
+    }
+
+    // the default bindings with any from the config laid over them, so a config need only list
+    // the intentions it rebinds
+    pub fn load_or_default() -> InputMap {
+        match config::read() {
+            Ok(Some(json)) => match serde_json::from_str(&json) {
+                Ok(overrides) => InputMap::default().merged(overrides),
+                Err(e) => {
+                    eprintln!("Could not read input map: {e}");
+                    InputMap::default()
+                }
+            },
+            Ok(None) => InputMap::default(),
+            Err(e) => {
+                eprintln!("Could not load input map: {e}");
+                InputMap::default()
+            }
+        }
+    }
+}
+
+// the input resources bindings are checked against
+pub struct PlayerInput<'a> {
+    pub keys: &'a Input<KeyCode>,
+    pub buttons: &'a Input<GamepadButton>,
+    pub gamepads: &'a Gamepads,
+}
+
+impl<'a> PlayerInput<'a> {
+    // gamepad buttons count regardless of which pad they were pressed on
+    pub fn just_pressed(&self, binding: &Binding) -> bool {
+        match *binding {
+            Binding::Key(key) => self.keys.just_pressed(key),
+            Binding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .any(|&pad| self.buttons.just_pressed(GamepadButton(pad, button))),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod config {
+    // relative, so resolved against the working directory the game is run from
+    const CONFIG_PATH: &str = "input_map.json";
+
+    pub fn read() -> Result<Option<String>, String> {
+        match std::fs::read_to_string(CONFIG_PATH) {
+            Ok(json) => Ok(Some(json)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod config {
+    const CONFIG_KEY: &str = "beverage-input-map";
+
+    pub fn read() -> Result<Option<String>, String> {
+        web_sys::window()
+            .ok_or("No window")?
+            .local_storage()
+            .map_err(|e| format!("{e:?}"))?
+            .ok_or("No local storage")?
+            .get_item(CONFIG_KEY)
+            .map_err(|e| format!("{e:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_through_json() {
+        let map = InputMap::default();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::from_str::<InputMap>(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn every_binding_is_described() {
+        let map = InputMap::default();
+        assert_eq!(map.describe(&Intention::Step), "Up / Pad DPadUp");
+        assert_eq!(
+            map.describe(&Intention::Shoot(hex2d::Coordinate::new(0, 0))),
+            ""
+        );
+    }
+
+    #[test]
+    fn loaded_bindings_only_replace_the_intentions_they_mention() {
+        let overrides: InputMap =
+            serde_json::from_str(r#"[{"intention": "Step", "inputs": [{"Key": "W"}]}]"#).unwrap();
+        let map = InputMap::default().merged(overrides);
+
+        assert_eq!(map.describe(&Intention::Step), "W");
+        assert_eq!(map.describe(&Intention::Backstep), "Down / Pad DPadDown");
+        assert_eq!(map.0.len(), InputMap::default().0.len());
+        assert_eq!(map.0[0].intention, Intention::Step);
+    }
+}
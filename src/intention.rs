@@ -1,14 +1,28 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 use hex2d::*;
+use serde::{Deserialize, Serialize};
 
+use crate::camera::{window_to_hex, HoveredHex, MainCamera};
 use crate::domain::actions::backstep::BackstepAction;
 use crate::domain::actions::end_turn::EndTurnAction;
 use crate::domain::actions::rotate::RotateAction;
+use crate::domain::actions::shoot::ShootAction;
 use crate::domain::actions::step::StepAction;
 use crate::domain::actions::strike::StrikeAction;
+use crate::domain::combat::RangedWeapon;
+use crate::domain::common::{Actor, Facing, HexPos};
 use crate::domain::turn_queue::*;
-use crate::turn_engine::actions::ActionQueue;
+use crate::input_map::{InputMap, PlayerInput};
+use crate::map::{MapTile, Terrain};
+use crate::pathfinding::{a_star, CostModel, Move, MoveCosts, TerrainCosts};
+use crate::render::map::TileVisibility;
+use crate::render::player_vision::PlayerVisibility;
+use crate::turn_engine::actions::{ActionQueue, AnyAction};
+use crate::turn_engine::undo::{RedoAction, UndoAction};
 
 #[derive(Component)]
 pub struct PlayerControlled;
@@ -18,51 +32,296 @@ pub struct IntentionPlugin;
 impl Plugin for IntentionPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<IntentionEvent>()
-            .add_system(ingame_keyboard_input.label(IntentionProducer))
+            .init_resource::<Targeting>()
+            .insert_resource(InputMap::load_or_default())
+            .add_system(ingame_input.label(IntentionProducer))
+            .add_system(pointer_input.label(IntentionProducer))
+            .add_system(targeting_input.label(IntentionProducer).after(ingame_input))
             .add_system(process_intention.after(IntentionProducer));
     }
 }
 
 struct IntentionEvent(Entity, Intention);
 
-#[derive(Debug)]
-enum Intention {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Intention {
     Step,
     Backstep,
     TurnLeft,
     TurnRight,
     Strike,
     EndTurn,
+    Undo,
+    Redo,
+    // enters or leaves targeting mode, rather than acting directly
+    Aim,
+    Shoot(Coordinate),
+}
+
+impl Intention {
+    pub fn to_action(&self, entity: Entity) -> Option<AnyAction> {
+        Some(match self {
+            Intention::TurnLeft => RotateAction::new(entity, Angle::Left).into(),
+            Intention::TurnRight => RotateAction::new(entity, Angle::Right).into(),
+            Intention::Step => StepAction::new(entity).into(),
+            Intention::Backstep => BackstepAction::new(entity).into(),
+            Intention::EndTurn => EndTurnAction::new(entity).into(),
+            Intention::Strike => StrikeAction::new(entity).into(),
+            Intention::Undo => UndoAction::new(entity).into(),
+            Intention::Redo => RedoAction::new(entity).into(),
+            Intention::Shoot(target) => ShootAction::new(entity, *target).into(),
+            Intention::Aim => return None,
+        })
+    }
+}
+
+// while aiming, the target can be moved to any hex within range instead of moving the player
+#[derive(Default)]
+pub struct Targeting {
+    pub active: bool,
+    pub target: Option<Coordinate>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, SystemLabel)]
 struct IntentionProducer;
 
-fn ingame_keyboard_input(
+fn ingame_input(
     keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    input_map: Res<InputMap>,
     players: Query<(), With<PlayerControlled>>,
     turn_queue: Res<TurnQueue>,
+    targeting: Res<Targeting>,
     mut ev_intention: EventWriter<IntentionEvent>,
 ) {
+    if targeting.active {
+        return;
+    }
     if let Some(&entity) = turn_queue.head() {
         if players.get(entity).is_ok() {
-            let intention = if keys.just_pressed(KeyCode::Left) {
-                Intention::TurnLeft
-            } else if keys.just_pressed(KeyCode::Right) {
-                Intention::TurnRight
-            } else if keys.just_pressed(KeyCode::Up) {
-                Intention::Step
-            } else if keys.just_pressed(KeyCode::Down) {
-                Intention::Backstep
-            } else if keys.just_pressed(KeyCode::E) {
-                Intention::EndTurn
-            } else if keys.just_pressed(KeyCode::Space) {
-                Intention::Strike
-            } else {
-                return;
+            let input = PlayerInput {
+                keys: &keys,
+                buttons: &buttons,
+                gamepads: &gamepads,
             };
-            ev_intention.send(IntentionEvent(entity, intention));
+            // aiming is handled by targeting_input
+            match input_map.pressed_intention(&input) {
+                Some(Intention::Aim) | None => {}
+                Some(intention) => ev_intention.send(IntentionEvent(entity, intention)),
+            }
+        }
+    }
+}
+
+// clicking or tapping a tile walks the player towards it, as far as their energy allows
+fn pointer_input(
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    players: Query<(&HexPos, &Facing, &Actor), With<PlayerControlled>>,
+    actors: Query<&HexPos, With<Actor>>,
+    tiles: Query<(&HexPos, &MapTile, &PlayerVisibility)>,
+    turn_queue: Res<TurnQueue>,
+    targeting: Res<Targeting>,
+    mut ev_intention: EventWriter<IntentionEvent>,
+) {
+    if targeting.active {
+        return;
+    }
+
+    let (window, camera) = match (windows.get_primary(), cameras.get_single()) {
+        (Some(window), Ok(camera)) => (window, camera),
+        _ => return,
+    };
+
+    let point = if mouse.just_pressed(MouseButton::Left) {
+        window.cursor_position()
+    } else {
+        touches.iter_just_released().next().map(|t| t.position())
+    };
+    let point = match point {
+        Some(point) => point,
+        None => return,
+    };
+
+    let (entity, &HexPos(pos), &Facing(facing), actor) = match turn_queue
+        .head()
+        .and_then(|&e| players.get(e).ok().map(|(p, f, a)| (e, p, f, a)))
+    {
+        Some(player) => player,
+        None => return,
+    };
+
+    let size = Vec2::new(window.width(), window.height());
+    let goal = window_to_hex(size, camera, point);
+    if goal == pos {
+        return;
+    }
+
+    // only route through tiles the player knows about and which nobody is standing on
+    let occupied: HashSet<Coordinate> = actors.iter().map(|p| p.0).collect();
+    let terrain: HashMap<Coordinate, Terrain> = tiles
+        .iter()
+        .filter(|(p, _, vis)| {
+            TileVisibility::from_vis(vis) != TileVisibility::Undiscovered
+                && !occupied.contains(&p.0)
+        })
+        .map(|(p, tile, _)| (p.0, tile.terrain))
+        .collect();
+
+    let moves = MoveCosts::for_entity(entity);
+    let model = TerrainCosts {
+        moves,
+        terrain: &terrain,
+    };
+    let path = match a_star(Position::new(pos, facing), goal, &model) {
+        Some(path) => path,
+        None => return,
+    };
+
+    // turns are free, so only make them on the way to a step the player can afford
+    let mut energy = actor.actions_remaining as i32;
+    let mut turns = Vec::new();
+    let mut current = Position::new(pos, facing);
+    for mov in path {
+        let cost = model.cost(current, mov).unwrap_or(i32::MAX);
+        current = mov.apply(current);
+        let step = match mov {
+            Move::TurnLeft => {
+                turns.push(Intention::TurnLeft);
+                continue;
+            }
+            Move::TurnRight => {
+                turns.push(Intention::TurnRight);
+                continue;
+            }
+            Move::StepForward => Intention::Step,
+            Move::StepBackward => Intention::Backstep,
+        };
+        if energy < cost {
+            break;
+        }
+        energy -= cost;
+        for turn in turns.drain(..) {
+            ev_intention.send(IntentionEvent(entity, turn));
+        }
+        ev_intention.send(IntentionEvent(entity, step));
+    }
+}
+
+fn targeting_input(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    hovered: Res<HoveredHex>,
+    input_map: Res<InputMap>,
+    players: Query<(&HexPos, &Facing, &RangedWeapon), With<PlayerControlled>>,
+    visible: Query<(&HexPos, &PlayerVisibility), (With<Actor>, Without<PlayerControlled>)>,
+    turn_queue: Res<TurnQueue>,
+    mut targeting: ResMut<Targeting>,
+    mut ev_intention: EventWriter<IntentionEvent>,
+) {
+    let (entity, &HexPos(from), &Facing(facing), weapon) = match turn_queue
+        .head()
+        .and_then(|&e| players.get(e).ok().map(|(p, f, w)| (e, p, f, w)))
+    {
+        Some(player) => player,
+        None => {
+            if targeting.active {
+                *targeting = Targeting::default();
+            }
+            return;
         }
+    };
+
+    let input = PlayerInput {
+        keys: &keys,
+        buttons: &buttons,
+        gamepads: &gamepads,
+    };
+    let pressed = |intention| input_map.just_pressed(&intention, &input);
+
+    if !targeting.active {
+        if pressed(Intention::Aim) {
+            targeting.active = true;
+        } else {
+            return;
+        }
+    } else if pressed(Intention::Aim) {
+        *targeting = Targeting::default();
+        return;
+    }
+
+    let in_range = |c: Coordinate| c != from && from.distance(c) <= weapon.range;
+
+    // enemies the player can currently see are offered as a quick cycle, nearest first
+    let mut candidates: Vec<Coordinate> = visible
+        .iter()
+        .filter(|(pos, vis)| vis.is_visible && in_range(pos.0))
+        .map(|(pos, _)| pos.0)
+        .collect();
+    candidates.sort_by_key(|c| (from.distance(*c), c.x, c.y));
+
+    if targeting.target.is_none() {
+        targeting.target = candidates
+            .first()
+            .copied()
+            .or_else(|| Some(from + facing).filter(|&c| in_range(c)));
+    }
+
+    let current = targeting
+        .target
+        .and_then(|t| candidates.iter().position(|&c| c == t));
+    let len = candidates.len();
+    let cycled = match current {
+        _ if len == 0 => None,
+        Some(i) if pressed(Intention::TurnRight) => Some((i + 1) % len),
+        Some(i) if pressed(Intention::TurnLeft) => Some((i + len - 1) % len),
+        None if pressed(Intention::TurnRight) => Some(0),
+        None if pressed(Intention::TurnLeft) => Some(len - 1),
+        _ => None,
+    };
+    if let Some(i) = cycled {
+        targeting.target = Some(candidates[i]);
+    }
+
+    // otherwise the target can be any hex in range, moved along the player's facing or with the
+    // pointer
+    let nudge = if pressed(Intention::Step) {
+        Some(facing)
+    } else if pressed(Intention::Backstep) {
+        Some(-facing)
+    } else {
+        None
+    };
+    if let Some(dir) = nudge {
+        let moved = targeting.target.unwrap_or(from) + dir;
+        if in_range(moved) {
+            targeting.target = Some(moved);
+        }
+    }
+
+    let pointed = hovered.0.filter(|&c| in_range(c));
+    if hovered.is_changed() && pointed.is_some() {
+        targeting.target = pointed;
+    }
+
+    let clicked =
+        mouse.just_pressed(MouseButton::Left) || touches.iter_just_released().next().is_some();
+    let shot = if clicked {
+        pointed
+    } else if pressed(Intention::Strike) {
+        targeting.target
+    } else {
+        None
+    };
+    if let Some(target) = shot {
+        ev_intention.send(IntentionEvent(entity, Intention::Shoot(target)));
+        *targeting = Targeting::default();
     }
 }
 
@@ -71,13 +330,8 @@ fn process_intention(
     mut ev_action: ResMut<ActionQueue>,
 ) {
     for IntentionEvent(entity, intention) in ev_intention.iter() {
-        match intention {
-            Intention::TurnLeft => ev_action.push(RotateAction::new(*entity, Angle::Left)),
-            Intention::TurnRight => ev_action.push(RotateAction::new(*entity, Angle::Right)),
-            Intention::Step => ev_action.push(StepAction::new(*entity)),
-            Intention::Backstep => ev_action.push(BackstepAction::new(*entity)),
-            Intention::EndTurn => ev_action.push(EndTurnAction::new(*entity)),
-            Intention::Strike => ev_action.push(StrikeAction::new(*entity)),
-        };
+        if let Some(action) = intention.to_action(*entity) {
+            ev_action.push_any(action);
+        }
     }
 }
@@ -1,21 +1,27 @@
-use bevy::prelude::*;
+use bevy::{app::PluginGroupBuilder, prelude::*};
 
 use bevy_easings::EasingsPlugin;
 use bevy_prototype_lyon::prelude::*;
 use domain::turn_queue::TurnQueuePlugin;
 use render::GameRenderPlugin;
-use scenario::{ArenaScenario, CaveScenario};
+use save::SaveGamePlugin;
+use scenario::{ArenaScenario, CaveScenario, SavedScenario};
 use ui::UIPlugin;
 
 pub mod ai;
 pub mod camera;
 pub mod component_index;
 pub mod domain;
+pub mod headless;
+pub mod input_map;
 pub mod intention;
 pub mod map;
 pub mod maths;
 pub mod pathfinding;
 pub mod render;
+pub mod replay;
+pub mod rng;
+pub mod save;
 pub mod scenario;
 pub mod spawn;
 pub mod turn_engine;
@@ -26,25 +32,44 @@ use camera::*;
 use domain::*;
 use intention::*;
 use map::*;
+use rng::GameRng;
 use serde::*;
 use turn_engine::*;
 use wasm_bindgen::prelude::*;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Scenario {
     Arena,
     Cave,
+    Saved,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RunParams {
-    scenario: Scenario,
+    pub scenario: Scenario,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl RunParams {
+    pub(crate) fn configure(&self, app: &mut App) {
+        let rng = self.seed.map_or_else(GameRng::from_entropy, GameRng::new);
+        info!("Running with seed {}", rng.seed());
+        app.insert_resource(rng);
+
+        match self.scenario {
+            Scenario::Arena => app.add_plugin(ArenaScenario),
+            Scenario::Cave => app.add_plugin(CaveScenario),
+            Scenario::Saved => app.add_plugin(SavedScenario),
+        };
+    }
 }
 
 impl Default for RunParams {
     fn default() -> Self {
         Self {
             scenario: Scenario::Arena,
+            seed: None,
         }
     }
 }
@@ -69,27 +94,35 @@ pub fn run(params: RunParams) {
         .add_plugin(EasingsPlugin)
         .add_plugin(GamePlugin);
 
-    match params.scenario {
-        Scenario::Arena => app.add_plugin(ArenaScenario),
-        Scenario::Cave => app.add_plugin(CaveScenario),
-    };
+    params.configure(&mut app);
 
     app.run();
 }
 
+// the game rules without any windowing, input or rendering
+pub struct SimulationPlugins;
+
+impl PluginGroup for SimulationPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(MapPlugin)
+            .add(TurnEnginePlugin)
+            .add(TurnQueuePlugin)
+            .add(DomainPlugin)
+            .add(AiPlugin);
+    }
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(CameraPlugin)
-            .add_plugin(MapPlugin)
-            .add_plugin(TurnEnginePlugin)
-            .add_plugin(TurnQueuePlugin)
-            .add_plugin(DomainPlugin)
-            .add_plugin(AiPlugin)
+        app.add_plugins(SimulationPlugins)
+            .add_plugin(CameraPlugin)
             .add_plugin(IntentionPlugin)
             .add_plugin(GameRenderPlugin)
             .add_plugin(UIPlugin)
+            .add_plugin(SaveGamePlugin)
             .add_system(bevy::input::system::exit_on_esc_system);
     }
 }
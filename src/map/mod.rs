@@ -8,22 +8,50 @@ use hex2d::{Direction as HexDirection, *};
 use itertools::iterate;
 use rand::prelude::*;
 
-use crate::{component_index::ComponentIndex, domain::common::HexPos};
+use crate::{
+    component_index::ComponentIndex,
+    domain::common::{Actor, HexPos},
+};
+
+pub use self::terrain::{OnEnter, Terrain, TerrainProperties};
+
+mod terrain;
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(ComponentIndex::<HexPos>::plugin());
+        app.add_plugin(ComponentIndex::<HexPos>::plugin())
+            .init_resource::<MapVersion>()
+            .add_system_to_stage(CoreStage::PostUpdate, track_map_version);
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum Terrain {
-    Floor,
-    Wall,
+// incremented whenever any tile is added, removed or changed, so derived data such as cached
+// paths can tell when it is stale
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapVersion(u32);
+
+impl MapVersion {
+    pub fn next(self) -> MapVersion {
+        MapVersion(self.0.wrapping_add(1))
+    }
 }
 
+fn track_map_version(
+    changed: Query<(), Changed<MapTile>>,
+    removed: RemovedComponents<MapTile>,
+    mut version: ResMut<MapVersion>,
+) {
+    if changed.iter().next().is_some() || removed.iter().next().is_some() {
+        *version = version.next();
+    }
+}
+
+// the parent of every MapTile
+#[derive(Component)]
+pub struct MapRoot;
+
 #[derive(Component)]
 pub struct MapTile {
     pub terrain: Terrain,
@@ -52,11 +80,12 @@ pub struct MapTiles<'w, 's> {
     query: Query<'w, 's, (&'static HexPos, &'static MapTile)>,
 }
 impl<'w, 's> MapTiles<'w, 's> {
-    pub fn get_walls(&self) -> HashSet<Coordinate> {
+    // every hex which cannot be seen through
+    pub fn get_opaque(&self) -> HashSet<Coordinate> {
         self.query
             .iter()
             .filter_map(|(c, t)| {
-                if t.terrain == Terrain::Wall {
+                if t.terrain.blocks_sight() {
                     Some(c.0)
                 } else {
                     None
@@ -65,11 +94,15 @@ impl<'w, 's> MapTiles<'w, 's> {
             .collect()
     }
 
-    pub fn get_floor(&self) -> HashSet<Coordinate> {
+    pub fn get_terrain(&self) -> HashMap<Coordinate, Terrain> {
+        self.query.iter().map(|(c, t)| (c.0, t.terrain)).collect()
+    }
+
+    pub fn get_walkable(&self) -> HashSet<Coordinate> {
         self.query
             .iter()
             .filter_map(|(x, t)| {
-                if t.terrain == Terrain::Floor {
+                if t.terrain.properties().is_walkable() {
                     Some(x.0)
                 } else {
                     None
@@ -79,8 +112,24 @@ impl<'w, 's> MapTiles<'w, 's> {
     }
 }
 
+// which hexes are currently stood on by an actor
+#[derive(SystemParam)]
+pub struct Occupancy<'w, 's> {
+    index: Res<'w, ComponentIndex<HexPos>>,
+    actors: Query<'w, 's, (), With<Actor>>,
+}
+impl<'w, 's> Occupancy<'w, 's> {
+    pub fn is_occupied(&self, coord: Coordinate) -> bool {
+        self.index
+            .get_entities(&HexPos(coord))
+            .map_or(false, |entities| {
+                entities.iter().any(|&e| self.actors.get(e).is_ok())
+            })
+    }
+}
+
 pub trait MapGenerator {
-    fn generate_map(&self) -> Map;
+    fn generate_map(&self, rng: &mut impl Rng) -> Map;
 }
 
 pub struct BasicHex {
@@ -94,7 +143,7 @@ impl BasicHex {
 }
 
 impl MapGenerator for BasicHex {
-    fn generate_map(&self) -> Map {
+    fn generate_map(&self, _rng: &mut impl Rng) -> Map {
         let mut cells = floor_hex(self.radius);
         surround_wall(&mut cells);
 
@@ -112,6 +161,15 @@ fn floor_hex(radius: usize) -> HashMap<Coordinate, MapCell> {
         .collect()
 }
 
+// a hexagon of plain floor centred on the origin, for tests which need somewhere to move or listen
+#[cfg(test)]
+pub fn open_floor(radius: i32) -> HashMap<Coordinate, Terrain> {
+    Coordinate::new(0, 0)
+        .range_iter(radius)
+        .map(|c| (c, Terrain::Floor))
+        .collect()
+}
+
 fn surround_wall(map: &mut HashMap<Coordinate, MapCell>) {
     let walls: HashSet<_> = map
         .iter()
@@ -130,8 +188,39 @@ fn surround_wall(map: &mut HashMap<Coordinate, MapCell>) {
     }
 }
 
-fn random_noise(coordinates: impl Iterator<Item = Coordinate>) -> HashMap<Coordinate, MapCell> {
-    let mut rng = thread_rng();
+// the chance of each hex out in the open being given some other terrain
+const FEATURE_CHANCE: f64 = 0.1;
+const FEATURES: [Terrain; 4] = [
+    Terrain::Water,
+    Terrain::Rubble,
+    Terrain::TallGrass,
+    Terrain::Chasm,
+];
+
+// swaps some of the floor for other terrain, leaving hexes next to anything but floor alone so that
+// no passage gets cut off
+fn scatter_features(cells: &mut HashMap<Coordinate, MapCell>, rng: &mut impl Rng) {
+    let open: Vec<_> = floor_coordinates(cells)
+        .into_iter()
+        .filter(|c| {
+            c.neighbors()
+                .iter()
+                .all(|n| cells.get(n).map_or(false, |n| n.terrain == Terrain::Floor))
+        })
+        .collect();
+
+    for c in open {
+        if rng.gen_bool(FEATURE_CHANCE) {
+            let terrain = *FEATURES.choose(rng).unwrap();
+            cells.insert(c, MapCell { terrain });
+        }
+    }
+}
+
+fn random_noise(
+    coordinates: impl Iterator<Item = Coordinate>,
+    rng: &mut impl Rng,
+) -> HashMap<Coordinate, MapCell> {
     coordinates
         .map(|c| {
             (
@@ -199,24 +288,30 @@ impl CellularAutomata {
     }
 }
 
-fn choose_random(cells: &HashMap<Coordinate, MapCell>) -> Coordinate {
-    let mut rng = thread_rng();
-    *cells
+// floor coordinates in a fixed order, so that choosing from them only depends on the rng
+pub fn floor_coordinates(cells: &HashMap<Coordinate, MapCell>) -> Vec<Coordinate> {
+    let mut floor: Vec<_> = cells
         .iter()
         .filter(|(_, c)| c.terrain == Terrain::Floor)
-        .map(|(c, _)| c)
-        .choose(&mut rng)
-        .unwrap()
+        .map(|(&c, _)| c)
+        .collect();
+    floor.sort_by_key(|c| (c.x, c.y));
+    floor
+}
+
+fn choose_random(cells: &HashMap<Coordinate, MapCell>, rng: &mut impl Rng) -> Coordinate {
+    *floor_coordinates(cells).choose(rng).unwrap()
 }
 
 impl MapGenerator for CellularAutomata {
-    fn generate_map(&self) -> Map {
-        let mut cells = random_noise(Coordinate::new(0, 0).range_iter(self.radius as i32));
+    fn generate_map(&self, rng: &mut impl Rng) -> Map {
+        let mut cells = random_noise(Coordinate::new(0, 0).range_iter(self.radius as i32), rng);
 
         self.process(&mut cells);
         surround_wall(&mut cells);
+        scatter_features(&mut cells, rng);
 
-        let player_start = choose_random(&cells);
+        let player_start = choose_random(&cells, rng);
 
         Map {
             cells,
@@ -238,38 +333,47 @@ impl DrunkardsWalk {
         }
     }
 
-    fn gen_path(&self, start: Coordinate) -> impl Iterator<Item = Coordinate> {
-        let mut rng = thread_rng();
+    fn gen_path<'a>(
+        &self,
+        start: Coordinate,
+        rng: &'a mut impl Rng,
+    ) -> impl Iterator<Item = Coordinate> + 'a {
         iterate(start, move |x| {
-            let dir = HexDirection::all().choose(&mut rng).unwrap();
+            let dir = HexDirection::all().choose(&mut *rng).unwrap();
             *x + *dir
         })
         .take(self.distance)
     }
 
-    fn carve_path(&self, start: Coordinate, map: &mut HashMap<Coordinate, MapCell>) {
-        let path = self.gen_path(start).map(|c| (c, MapCell::floor()));
+    fn carve_path(
+        &self,
+        start: Coordinate,
+        map: &mut HashMap<Coordinate, MapCell>,
+        rng: &mut impl Rng,
+    ) {
+        let path = self.gen_path(start, rng).map(|c| (c, MapCell::floor()));
         map.extend(path);
     }
 }
 
 impl MapGenerator for DrunkardsWalk {
-    fn generate_map(&self) -> Map {
+    fn generate_map(&self, rng: &mut impl Rng) -> Map {
         let mut path_start = Coordinate::new(0, 0);
 
         let mut cells = HashMap::<Coordinate, MapCell>::default();
 
         loop {
-            self.carve_path(path_start, &mut cells);
+            self.carve_path(path_start, &mut cells, rng);
             if cells.len() > self.limit {
                 break;
             }
-            path_start = choose_random(&cells);
+            path_start = choose_random(&cells, rng);
         }
 
         surround_wall(&mut cells);
+        scatter_features(&mut cells, rng);
 
-        let player_start = choose_random(&cells);
+        let player_start = choose_random(&cells, rng);
 
         Map {
             cells,
@@ -277,3 +381,50 @@ impl MapGenerator for DrunkardsWalk {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::GameRng;
+
+    use super::*;
+
+    #[test]
+    fn same_seed_generates_same_map() {
+        let generator = DrunkardsWalk::example();
+
+        let a = generator.generate_map(&mut GameRng::new(42));
+        let b = generator.generate_map(&mut GameRng::new(42));
+
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.player_start, b.player_start);
+    }
+
+    #[test]
+    fn same_seed_generates_same_cellular_automata() {
+        let generator = CellularAutomata::example();
+
+        let a = generator.generate_map(&mut GameRng::new(7));
+        let b = generator.generate_map(&mut GameRng::new(7));
+
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.player_start, b.player_start);
+    }
+
+    #[test]
+    fn features_are_only_scattered_out_in_the_open() {
+        let map = CellularAutomata::example().generate_map(&mut GameRng::new(3));
+
+        let features: Vec<_> = map
+            .cells
+            .iter()
+            .filter(|(_, cell)| FEATURES.contains(&cell.terrain))
+            .collect();
+
+        for (c, _) in features {
+            for n in c.neighbors() {
+                assert_ne!(map.cells.get(&n).map(|n| n.terrain), Some(Terrain::Wall));
+            }
+        }
+        assert_eq!(map.cells[&map.player_start].terrain, Terrain::Floor);
+    }
+}
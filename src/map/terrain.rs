@@ -0,0 +1,115 @@
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Terrain {
+    Floor,
+    Wall,
+    Water,
+    Rubble,
+    TallGrass,
+    Chasm,
+}
+
+// what happens to an actor as it moves onto a terrain
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OnEnter {
+    // makes a noise this loud on top of their footsteps
+    Noise(u8),
+    // uses up the rest of their energy for the turn
+    Exhaust,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainProperties {
+    // the extra energy it costs to move onto this terrain, or `None` if it cannot be entered
+    pub move_cost: Option<i32>,
+    pub blocks_sight: bool,
+    pub on_enter: Option<OnEnter>,
+    pub colour: Color,
+}
+
+impl TerrainProperties {
+    pub fn is_walkable(&self) -> bool {
+        self.move_cost.is_some()
+    }
+
+    // can be neither entered nor seen through, so stops shots and sound as well
+    pub fn is_solid(&self) -> bool {
+        !self.is_walkable() && self.blocks_sight
+    }
+}
+
+impl Terrain {
+    // how every kind of terrain behaves
+    pub fn properties(self) -> &'static TerrainProperties {
+        match self {
+            Terrain::Floor => &TerrainProperties {
+                move_cost: Some(0),
+                blocks_sight: false,
+                on_enter: None,
+                colour: Color::OLIVE,
+            },
+            Terrain::Wall => &TerrainProperties {
+                move_cost: None,
+                blocks_sight: true,
+                on_enter: None,
+                colour: Color::MIDNIGHT_BLUE,
+            },
+            // slow to wade into, and takes the rest of the turn besides
+            Terrain::Water => &TerrainProperties {
+                move_cost: Some(1),
+                blocks_sight: false,
+                on_enter: Some(OnEnter::Exhaust),
+                colour: Color::TEAL,
+            },
+            Terrain::Rubble => &TerrainProperties {
+                move_cost: Some(1),
+                blocks_sight: false,
+                on_enter: Some(OnEnter::Noise(5)),
+                colour: Color::GRAY,
+            },
+            Terrain::TallGrass => &TerrainProperties {
+                move_cost: Some(0),
+                blocks_sight: true,
+                on_enter: None,
+                colour: Color::DARK_GREEN,
+            },
+            Terrain::Chasm => &TerrainProperties {
+                move_cost: None,
+                blocks_sight: false,
+                on_enter: None,
+                colour: Color::BLACK,
+            },
+        }
+    }
+
+    pub fn move_cost(&self) -> Option<i32> {
+        self.properties().move_cost
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        self.properties().blocks_sight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_is_slower_to_cross_than_floor() {
+        assert!(Terrain::Water.move_cost() > Terrain::Floor.move_cost());
+        assert_eq!(Terrain::Water.properties().on_enter, Some(OnEnter::Exhaust));
+    }
+
+    #[test]
+    fn walkability_and_sight_are_independent() {
+        assert!(Terrain::TallGrass.properties().is_walkable());
+        assert!(Terrain::TallGrass.blocks_sight());
+        assert!(!Terrain::Chasm.properties().is_walkable());
+        assert!(!Terrain::Chasm.blocks_sight());
+        assert!(!Terrain::Chasm.properties().is_solid());
+        assert!(Terrain::Wall.properties().is_solid());
+    }
+}
@@ -4,8 +4,9 @@ use std::{
 };
 
 use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Debug, PartialOrd)]
+#[derive(Clone, Copy, PartialEq, Debug, PartialOrd, Serialize, Deserialize)]
 pub struct Radians(f32);
 
 impl Radians {
@@ -40,7 +41,9 @@ impl Add for Radians {
 
 pub const RADIANS_0DEG: Radians = Radians(0.0);
 pub const RADIANS_60DEG: Radians = Radians(60.0 * PI / 180.0);
+pub const RADIANS_90DEG: Radians = Radians(90.0 * PI / 180.0);
 pub const RADIANS_120DEG: Radians = Radians(120.0 * PI / 180.0);
+pub const RADIANS_150DEG: Radians = Radians(150.0 * PI / 180.0);
 pub const RADIANS_180DEG: Radians = Radians(PI);
 
 const SQRT_3: f32 = 1.73205080757;
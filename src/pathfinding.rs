@@ -1,13 +1,20 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
 
-use bevy::utils::{HashMap, HashSet};
-use hex2d::{Angle, Coordinate, Position};
+use bevy::prelude::Entity;
+use bevy::utils::HashMap;
+use hex2d::{Angle, Coordinate, Direction, Position};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use crate::domain::actions::{backstep::BackstepAction, rotate::RotateAction, step::StepAction};
+use crate::map::{MapVersion, OnEnter, Terrain};
+use crate::turn_engine::actions::Action;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Move {
     TurnLeft,
     TurnRight,
     StepForward,
+    StepBackward,
 }
 
 impl Move {
@@ -16,16 +23,147 @@ impl Move {
             Move::TurnLeft => pos + Angle::Left,
             Move::TurnRight => pos + Angle::Right,
             Move::StepForward => pos + Coordinate::from(pos.dir),
+            Move::StepBackward => pos + Coordinate::from(pos.dir + Angle::Back),
         }
     }
+}
 
-    pub fn cost(&self) -> i32 {
-        match self {
-            Move::TurnLeft => 1,
-            Move::TurnRight => 1,
-            Move::StepForward => 1,
+const MOVES: [Move; 4] = [
+    Move::TurnLeft,
+    Move::TurnRight,
+    Move::StepForward,
+    Move::StepBackward,
+];
+
+// what each kind of move costs an actor, taken from the actions which perform them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MoveCosts {
+    pub turn: i32,
+    pub step: i32,
+    pub backstep: i32,
+}
+
+impl MoveCosts {
+    pub fn for_entity(entity: Entity) -> MoveCosts {
+        MoveCosts {
+            turn: RotateAction::new(entity, Angle::Left).cost() as i32,
+            step: StepAction::new(entity).cost() as i32,
+            backstep: BackstepAction::new(entity).cost() as i32,
+        }
+    }
+
+    pub fn of(&self, mov: Move) -> i32 {
+        match mov {
+            Move::TurnLeft | Move::TurnRight => self.turn,
+            Move::StepForward => self.step,
+            Move::StepBackward => self.backstep,
+        }
+    }
+}
+
+pub trait CostModel {
+    // the cost of making `mov` from `from`, or `None` if the move is not allowed
+    fn cost(&self, from: Position, mov: Move) -> Option<i32>;
+
+    // a lower bound on the cost of moving one hex, so that the heuristic never overestimates
+    fn min_step_cost(&self) -> i32;
+}
+
+// stepping onto terrain which ends the turn uses up whatever energy is left, which is not known
+// while planning, so it is priced as a whole turn's worth
+const EXHAUSTION_COST: i32 = 2;
+
+// moves cost what their actions do, plus whatever the destination terrain charges
+pub struct TerrainCosts<'a> {
+    pub moves: MoveCosts,
+    pub terrain: &'a HashMap<Coordinate, Terrain>,
+}
+
+impl<'a> CostModel for TerrainCosts<'a> {
+    fn cost(&self, from: Position, mov: Move) -> Option<i32> {
+        let to = mov.apply(from);
+        let terrain = if to.coord == from.coord {
+            0
+        } else {
+            let properties = self.terrain.get(&to.coord)?.properties();
+            let on_enter = match properties.on_enter {
+                Some(OnEnter::Exhaust) => EXHAUSTION_COST,
+                _ => 0,
+            };
+            properties.move_cost? + on_enter
+        };
+        Some(self.moves.of(mov) + terrain)
+    }
+
+    fn min_step_cost(&self) -> i32 {
+        self.moves.step.min(self.moves.backstep).max(0)
+    }
+}
+
+// costs what `inner` does, except that hexes for which `blocked` holds cannot be entered
+pub struct Avoiding<M, F> {
+    pub inner: M,
+    pub blocked: F,
+}
+
+impl<M: CostModel, F: Fn(Coordinate) -> bool> CostModel for Avoiding<M, F> {
+    fn cost(&self, from: Position, mov: Move) -> Option<i32> {
+        let to = mov.apply(from);
+        if to.coord != from.coord && (self.blocked)(to.coord) {
+            return None;
+        }
+        self.inner.cost(from, mov)
+    }
+
+    fn min_step_cost(&self) -> i32 {
+        self.inner.min_step_cost()
+    }
+}
+
+// where following `path` from `start` ends up
+pub fn destination(start: Position, path: &VecDeque<Move>) -> Position {
+    path.iter().fold(start, |pos, mov| mov.apply(pos))
+}
+
+// cuts `path` short before its first step into a blocked hex, returning whether it did so
+pub fn truncate_at_blocked(
+    start: Position,
+    path: &mut VecDeque<Move>,
+    blocked: impl Fn(Coordinate) -> bool,
+) -> bool {
+    let mut pos = start;
+    for (i, mov) in path.iter().enumerate() {
+        pos = mov.apply(pos);
+        if blocked(pos.coord) {
+            path.truncate(i);
+            return true;
         }
     }
+    false
+}
+
+// the hexes AI actors intend to step into on their next turn, so that others can plan around them
+//
+// each actor holds at most one claim, which it replaces whenever it plans again
+#[derive(Default)]
+pub struct Reservations(HashMap<Entity, Coordinate>);
+
+impl Reservations {
+    pub fn reserve(&mut self, entity: Entity, coord: Coordinate) {
+        self.0.insert(entity, coord);
+    }
+
+    pub fn release(&mut self, entity: Entity) {
+        self.0.remove(&entity);
+    }
+
+    pub fn retain(&mut self, keep: impl Fn(Entity) -> bool) {
+        self.0.retain(|&e, _| keep(e));
+    }
+
+    pub fn is_reserved_for_other(&self, coord: Coordinate, entity: Entity) -> bool {
+        self.0.iter().any(|(&e, &c)| c == coord && e != entity)
+    }
 }
 
 fn retrace(
@@ -44,16 +182,20 @@ fn retrace(
     path
 }
 
-const MOVES: [Move; 3] = [Move::TurnLeft, Move::TurnRight, Move::StepForward];
+// orders open nodes by estimated total cost, breaking ties by position so that the chosen path
+// does not depend on hash order
+type OpenNode = Reverse<(i32, i32, i32, i32)>;
 
-pub fn a_star<F: Fn(&Coordinate) -> bool>(
-    start: Position,
-    goal: Coordinate,
-    is_valid: F,
-) -> Option<VecDeque<Move>> {
-    // set of discovered nodes which need to be expanded
-    let mut to_search = HashSet::default();
-    to_search.insert(start);
+fn open_node(f_score: i32, pos: Position) -> OpenNode {
+    Reverse((f_score, pos.coord.x, pos.coord.y, pos.dir as i32))
+}
+
+pub fn a_star(start: Position, goal: Coordinate, model: &impl CostModel) -> Option<VecDeque<Move>> {
+    let heuristic = |pos: Position| pos.coord.distance(goal) * model.min_step_cost();
+
+    // discovered nodes which need to be expanded; stale entries are skipped when popped
+    let mut to_search = BinaryHeap::new();
+    to_search.push(open_node(heuristic(start), start));
 
     // for each Position, stores the node immediately preceding it on the cheapest path from start
     let mut came_from = HashMap::<Position, (Position, Move)>::default();
@@ -62,42 +204,291 @@ pub fn a_star<F: Fn(&Coordinate) -> bool>(
     let mut g_score = HashMap::<Position, i32>::default();
     g_score.insert(start, 0);
 
-    // estimated cheapest cost from start to goal via <Position>
-    let mut f_score = HashMap::<Position, i32>::default();
-    f_score.insert(start, start.coord.distance(goal));
-
-    loop {
-        if to_search.is_empty() {
-            break;
+    while let Some(Reverse((f_score, x, y, dir))) = to_search.pop() {
+        let current = Position::new(Coordinate::new(x, y), Direction::from_int(dir));
+        if f_score > g_score[&current] + heuristic(current) {
+            continue;
         }
 
-        let current = *to_search.iter().min_by_key(|x| f_score[x]).unwrap();
-
         if current.coord == goal {
             return Some(retrace(&mut came_from, current));
         }
 
-        to_search.remove(&current);
-
         for mov in MOVES {
-            let next_pos = mov.apply(current);
-            if !is_valid(&next_pos.coord) {
-                continue;
-            }
+            let cost = match model.cost(current, mov) {
+                Some(cost) => cost,
+                None => continue,
+            };
 
-            let tentative_g_score = g_score[&current] + mov.cost();
+            let next_pos = mov.apply(current);
+            let tentative_g_score = g_score[&current] + cost;
             if g_score
                 .get(&next_pos)
                 .map_or(true, |x| tentative_g_score < *x)
             {
                 came_from.insert(next_pos, (current, mov));
                 g_score.insert(next_pos, tentative_g_score);
-                f_score.insert(next_pos, tentative_g_score + next_pos.coord.distance(goal));
-
-                to_search.insert(next_pos);
+                to_search.push(open_node(tentative_g_score + heuristic(next_pos), next_pos));
             }
         }
     }
 
     None
 }
+
+// how many paths are kept before the least recently used is dropped
+const MAX_CACHED_PATHS: usize = 256;
+
+type PathKey = (Position, Coordinate, MoveCosts);
+
+// paths found by `a_star` over the map's terrain, which stay valid until the map next changes
+#[derive(Default)]
+pub struct PathCache {
+    version: Option<MapVersion>,
+    // each path with when it was last asked for
+    paths: HashMap<PathKey, (u64, Option<VecDeque<Move>>)>,
+    uses: u64,
+}
+
+impl PathCache {
+    // `terrain` is only called when the path has not already been found
+    pub fn find(
+        &mut self,
+        version: MapVersion,
+        start: Position,
+        goal: Coordinate,
+        moves: MoveCosts,
+        terrain: impl FnOnce() -> HashMap<Coordinate, Terrain>,
+    ) -> Option<VecDeque<Move>> {
+        if self.version != Some(version) {
+            self.paths.clear();
+            self.version = Some(version);
+        }
+
+        self.uses += 1;
+        let key = (start, goal, moves);
+        if let Some((used, path)) = self.paths.get_mut(&key) {
+            *used = self.uses;
+            return path.clone();
+        }
+
+        let terrain = terrain();
+        let path = a_star(
+            start,
+            goal,
+            &TerrainCosts {
+                moves,
+                terrain: &terrain,
+            },
+        );
+
+        if self.paths.len() >= MAX_CACHED_PATHS {
+            let oldest = self
+                .paths
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.paths.remove(&oldest);
+            }
+        }
+        self.paths.insert(key, (self.uses, path.clone()));
+        path
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::map::open_floor;
+
+    use super::*;
+
+    const COSTS: MoveCosts = MoveCosts {
+        turn: 0,
+        step: 1,
+        backstep: 2,
+    };
+
+    fn path_cost(start: Position, path: &VecDeque<Move>, model: &impl CostModel) -> i32 {
+        let mut pos = start;
+        let mut total = 0;
+        for &mov in path {
+            total += model.cost(pos, mov).unwrap();
+            pos = mov.apply(pos);
+        }
+        total
+    }
+
+    #[test]
+    fn free_turns_are_preferred_over_backstepping() {
+        let terrain = open_floor(3);
+        let model = TerrainCosts {
+            moves: COSTS,
+            terrain: &terrain,
+        };
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let behind = start.coord + (start.dir + Angle::Back);
+
+        let path = a_star(start, behind, &model).unwrap();
+        assert_eq!(path_cost(start, &path, &model), 1);
+        assert!(!path.contains(&Move::StepBackward));
+    }
+
+    #[test]
+    fn costly_turns_make_backstepping_worthwhile() {
+        let terrain = open_floor(3);
+        let moves = MoveCosts { turn: 1, ..COSTS };
+        let model = TerrainCosts {
+            moves,
+            terrain: &terrain,
+        };
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let behind = start.coord + (start.dir + Angle::Back);
+
+        let path = a_star(start, behind, &model).unwrap();
+        assert_eq!(path, VecDeque::from(vec![Move::StepBackward]));
+    }
+
+    #[test]
+    fn walls_are_avoided() {
+        let mut terrain = open_floor(3);
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let ahead = start.coord + start.dir;
+        terrain.insert(ahead, Terrain::Wall);
+        let model = TerrainCosts {
+            moves: COSTS,
+            terrain: &terrain,
+        };
+
+        let goal = ahead + start.dir;
+        let path = a_star(start, goal, &model).unwrap();
+
+        let mut pos = start;
+        for mov in path {
+            pos = mov.apply(pos);
+            assert_ne!(pos.coord, ahead);
+        }
+        assert_eq!(pos.coord, goal);
+    }
+
+    #[test]
+    fn short_detours_are_preferred_over_wading() {
+        let mut terrain = open_floor(3);
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let ahead = start.coord + start.dir;
+        terrain.insert(ahead, Terrain::Water);
+        let model = TerrainCosts {
+            moves: COSTS,
+            terrain: &terrain,
+        };
+
+        let goal = ahead + start.dir;
+        let path = a_star(start, goal, &model).unwrap();
+
+        let mut pos = start;
+        for mov in path {
+            pos = mov.apply(pos);
+            assert_ne!(pos.coord, ahead);
+        }
+        assert_eq!(pos.coord, goal);
+    }
+
+    #[test]
+    fn blocked_hexes_are_routed_around() {
+        let terrain = open_floor(3);
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let ahead = start.coord + start.dir;
+        let goal = ahead + start.dir;
+        let model = Avoiding {
+            inner: TerrainCosts {
+                moves: COSTS,
+                terrain: &terrain,
+            },
+            blocked: |c| c == ahead,
+        };
+
+        let mut path = a_star(start, goal, &model).unwrap();
+        assert!(!truncate_at_blocked(start, &mut path, |c| c == ahead));
+
+        let mut direct = VecDeque::from(vec![Move::StepForward, Move::StepForward]);
+        assert!(truncate_at_blocked(start, &mut direct, |c| c == ahead));
+        assert!(direct.is_empty());
+    }
+
+    #[test]
+    fn reservations_only_block_other_actors() {
+        let mut reservations = Reservations::default();
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        let c = Coordinate::new(1, 1);
+
+        reservations.reserve(a, c);
+        assert!(reservations.is_reserved_for_other(c, b));
+        assert!(!reservations.is_reserved_for_other(c, a));
+
+        reservations.reserve(a, Coordinate::new(2, 2));
+        assert!(!reservations.is_reserved_for_other(c, b));
+    }
+
+    #[test]
+    fn cache_reuses_paths_until_the_map_changes() {
+        let terrain = open_floor(3);
+        let calls = Cell::new(0);
+        let lookup = || {
+            calls.set(calls.get() + 1);
+            terrain.clone()
+        };
+
+        let mut cache = PathCache::default();
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let goal = Coordinate::new(2, -1);
+        let version = MapVersion::default();
+
+        let first = cache.find(version, start, goal, COSTS, lookup);
+        let second = cache.find(version, start, goal, COSTS, lookup);
+        assert_eq!(first, second);
+        assert_eq!(calls.get(), 1);
+
+        cache.find(MapVersion::next(version), start, goal, COSTS, lookup);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn cache_drops_the_least_recently_used_path_when_full() {
+        let terrain = open_floor(3);
+        let calls = Cell::new(0);
+        let lookup = || {
+            calls.set(calls.get() + 1);
+            terrain.clone()
+        };
+
+        let mut cache = PathCache::default();
+        let start = Position::new(Coordinate::new(0, 0), Direction::YZ);
+        let version = MapVersion::default();
+        let goal = |i: usize| Coordinate::new(i as i32, 0);
+
+        for i in 0..MAX_CACHED_PATHS {
+            cache.find(version, start, goal(i), COSTS, lookup);
+        }
+        // the first path is used again, so the second is now the oldest
+        cache.find(version, start, goal(0), COSTS, lookup);
+        cache.find(version, start, goal(MAX_CACHED_PATHS), COSTS, lookup);
+        assert_eq!(cache.len(), MAX_CACHED_PATHS);
+        assert_eq!(calls.get(), MAX_CACHED_PATHS + 1);
+
+        cache.find(version, start, goal(0), COSTS, lookup);
+        assert_eq!(calls.get(), MAX_CACHED_PATHS + 1);
+        cache.find(version, start, goal(1), COSTS, lookup);
+        assert_eq!(calls.get(), MAX_CACHED_PATHS + 2);
+    }
+}
@@ -1,17 +1,52 @@
 use bevy::prelude::*;
 use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*, shapes::Circle};
 
-use crate::domain::common::{Facing, HexPos, HEX_SPACING};
+use crate::{
+    ai::awareness::{AlertLevel, Awareness},
+    domain::common::{Actor, Facing, HexPos, HEX_SPACING},
+    Player,
+};
 
 use super::player_vision::{PlayerVisibility, PlayerVisionUpdate, VisibilityMemory};
 
 pub struct ActorRenderPlugin;
 impl Plugin for ActorRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_to_stage(
-            CoreStage::PostUpdate,
-            actor_visibility.after(PlayerVisionUpdate),
-        );
+        app.add_system(attach_actor_shapes)
+            .add_system(show_awareness)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                actor_visibility.after(PlayerVisionUpdate),
+            );
+    }
+}
+
+fn attach_actor_shapes(
+    mut commands: Commands,
+    query: Query<(Entity, &HexPos, &Facing, Option<&Player>), Added<Actor>>,
+) {
+    for (entity, pos, facing, player) in query.iter() {
+        let shape = if player.is_some() {
+            render_player(pos, facing)
+        } else {
+            render_enemy(pos, facing)
+        };
+        commands.entity(entity).insert_bundle(shape);
+    }
+}
+
+// enemies turn from yellow through orange to red as they notice the player
+fn show_awareness(mut query: Query<(&mut DrawMode, &Awareness), Changed<Awareness>>) {
+    for (mut draw, awareness) in query.iter_mut() {
+        let color = match awareness.level() {
+            AlertLevel::Unaware => Color::YELLOW,
+            AlertLevel::Suspicious => Color::ORANGE,
+            AlertLevel::Alerted => Color::RED,
+        };
+        *draw = DrawMode::Outlined {
+            fill_mode: FillMode::color(color),
+            outline_mode: StrokeMode::new(Color::BLACK, 1.0),
+        };
     }
 }
 
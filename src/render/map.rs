@@ -2,8 +2,11 @@ use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
 use hex2d::Coordinate;
 
 use crate::{
-    domain::common::HEX_SPACING,
-    map::{MapTile, Terrain},
+    domain::{
+        common::{HexPos, HEX_SPACING},
+        light::LightSource,
+    },
+    map::MapTile,
 };
 
 use super::player_vision::{PlayerVisibility, PlayerVisionUpdate, VisibilityMemory};
@@ -12,18 +15,37 @@ use bevy::prelude::*;
 pub struct MapRenderPlugin;
 impl Plugin for MapRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_to_stage(
+        app.add_system(attach_tile_shapes).add_system_to_stage(
             CoreStage::PostUpdate,
             update_map_visibility.after(PlayerVisionUpdate),
         );
     }
 }
 
+fn attach_tile_shapes(
+    mut commands: Commands,
+    query: Query<(Entity, &HexPos, &MapTile, Option<&LightSource>), Added<MapTile>>,
+) {
+    for (entity, &HexPos(c), tile, light) in query.iter() {
+        commands
+            .entity(entity)
+            .insert_bundle(tile_render_bundle(c, tile, light));
+    }
+}
+
 fn update_map_visibility(
-    mut query: Query<(&MapTile, &PlayerVisibility, &mut DrawMode), Changed<PlayerVisibility>>,
+    mut query: Query<
+        (
+            &MapTile,
+            Option<&LightSource>,
+            &PlayerVisibility,
+            &mut DrawMode,
+        ),
+        Changed<PlayerVisibility>,
+    >,
 ) {
-    for (tile, vis, mut draw) in query.iter_mut() {
-        *draw = get_draw_mode(tile, TileVisibility::from_vis(vis));
+    for (tile, light, vis, mut draw) in query.iter_mut() {
+        *draw = get_draw_mode(tile, light, TileVisibility::from_vis(vis));
     }
 }
 
@@ -46,10 +68,11 @@ impl TileVisibility {
     }
 }
 
-fn get_draw_mode(tile: &MapTile, vis: TileVisibility) -> DrawMode {
-    let mut color = match tile.terrain {
-        Terrain::Floor => Color::OLIVE,
-        Terrain::Wall => Color::MIDNIGHT_BLUE,
+// tiles which give off light are drawn as fires
+fn get_draw_mode(tile: &MapTile, light: Option<&LightSource>, vis: TileVisibility) -> DrawMode {
+    let mut color = match light {
+        Some(_) => Color::GOLD,
+        None => tile.terrain.properties().colour,
     };
 
     match vis {
@@ -70,8 +93,12 @@ fn get_draw_mode(tile: &MapTile, vis: TileVisibility) -> DrawMode {
     }
 }
 
-pub fn tile_render_bundle(c: Coordinate, tile: &MapTile) -> ShapeBundle {
-    let draw_mode = get_draw_mode(tile, TileVisibility::Undiscovered);
+pub fn tile_render_bundle(
+    c: Coordinate,
+    tile: &MapTile,
+    light: Option<&LightSource>,
+) -> ShapeBundle {
+    let draw_mode = get_draw_mode(tile, light, TileVisibility::Undiscovered);
     GeometryBuilder::build_as(&make_hex_tile(c), draw_mode, Transform::default())
 }
 
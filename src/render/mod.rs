@@ -2,13 +2,14 @@ use bevy::prelude::*;
 
 use self::{
     actor::ActorRenderPlugin, animation::AnimationPlugin, map::MapRenderPlugin,
-    player_vision::PlayerVisionPlugin,
+    player_vision::PlayerVisionPlugin, reticle::ReticlePlugin,
 };
 
 pub mod actor;
 pub mod animation;
 pub mod map;
 pub mod player_vision;
+pub mod reticle;
 
 pub struct GameRenderPlugin;
 impl Plugin for GameRenderPlugin {
@@ -18,6 +19,7 @@ impl Plugin for GameRenderPlugin {
             .add_plugin(MapRenderPlugin)
             .add_plugin(ActorRenderPlugin)
             .add_plugin(PlayerVisionPlugin)
-            .add_plugin(AnimationPlugin);
+            .add_plugin(AnimationPlugin)
+            .add_plugin(ReticlePlugin);
     }
 }
@@ -1,9 +1,12 @@
 use bevy::prelude::*;
-use hex2d::Position;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    domain::{common::*, vision::Vision},
-    map::MapTiles,
+    domain::{
+        common::*,
+        fov::{FieldOfView, FieldOfViewUpdate},
+    },
+    turn_engine::undo::UndoHistory,
     Player,
 };
 
@@ -13,7 +16,13 @@ impl Plugin for PlayerVisionPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_to_stage(
             CoreStage::PostUpdate,
-            update_player_visibility.label(PlayerVisionUpdate),
+            update_player_visibility
+                .label(PlayerVisionUpdate)
+                .after(FieldOfViewUpdate),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            seal_undo_on_sighting.after(PlayerVisionUpdate),
         );
     }
 }
@@ -21,13 +30,13 @@ impl Plugin for PlayerVisionPlugin {
 #[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
 pub struct PlayerVisionUpdate;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum VisibilityMemory {
     Transient,
     Persistent { seen: bool },
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerVisibility {
     pub is_visible: bool,
     pub memory: VisibilityMemory,
@@ -59,18 +68,13 @@ impl PlayerVisibility {
 }
 
 fn update_player_visibility(
-    player: Query<(&HexPos, &Facing, &Vision), With<Player>>,
+    player: Query<&FieldOfView, With<Player>>,
     positioned: Query<(&HexPos, Entity)>,
-    map: MapTiles,
     mut visibilities: Query<&mut PlayerVisibility>,
 ) {
-    if let Ok((&HexPos(player_coord), &Facing(player_dir), vision)) = player.get_single() {
-        let player_pos = Position::new(player_coord, player_dir);
-
-        let walls = map.get_walls();
-
+    if let Ok(fov) = player.get_single() {
         for (&HexPos(pos), entity) in positioned.iter() {
-            let is_visible = vision.can_see_relative(player_pos, pos, |x| walls.contains(&x));
+            let is_visible = fov.can_see(pos);
 
             if let Ok(mut visibility) = visibilities.get_mut(entity) {
                 if visibility.is_visible != is_visible {
@@ -80,3 +84,16 @@ fn update_player_visibility(
         }
     }
 }
+
+// undoing past the moment an actor came into view would let the player scout for free
+fn seal_undo_on_sighting(
+    sighted: Query<&PlayerVisibility, Changed<PlayerVisibility>>,
+    mut history: ResMut<UndoHistory>,
+) {
+    if sighted
+        .iter()
+        .any(|vis| vis.is_visible && vis.memory == VisibilityMemory::Transient)
+    {
+        history.seal();
+    }
+}
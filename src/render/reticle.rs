@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
+use hex2d::Coordinate;
+
+use crate::{
+    camera::HoveredHex,
+    domain::common::{HexPos, HEX_SPACING},
+    intention::Targeting,
+};
+
+// outlines drawn over the map: the hex the player is aiming at, and the hex under the pointer
+pub struct ReticlePlugin;
+impl Plugin for ReticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_outlines)
+            .add_system(update_reticle)
+            .add_system(update_hover_highlight);
+    }
+}
+
+#[derive(Component)]
+pub struct Reticle;
+
+#[derive(Component)]
+pub struct HoverHighlight;
+
+fn outline_bundle(color: Color, width: f32) -> ShapeBundle {
+    let shape = RegularPolygon {
+        sides: 6,
+        feature: RegularPolygonFeature::Radius(36.0),
+        center: Vec2::ZERO,
+    };
+
+    GeometryBuilder::build_as(
+        &shape,
+        DrawMode::Stroke(StrokeMode::new(color, width)),
+        Transform::default(),
+    )
+}
+
+fn spawn_outlines(mut commands: Commands) {
+    commands
+        .spawn_bundle(outline_bundle(Color::ORANGE_RED, 3.0))
+        .insert(Reticle)
+        .insert(Visibility { is_visible: false });
+
+    commands
+        .spawn_bundle(outline_bundle(Color::rgba(1.0, 1.0, 1.0, 0.6), 2.0))
+        .insert(HoverHighlight)
+        .insert(Visibility { is_visible: false });
+}
+
+fn place_outline(transform: &mut Transform, visibility: &mut Visibility, hex: Option<Coordinate>) {
+    match hex {
+        Some(hex) => {
+            // drawn above the tiles and actors
+            transform.translation = HexPos(hex).as_translation(HEX_SPACING) + Vec3::Z;
+            visibility.is_visible = true;
+        }
+        None => visibility.is_visible = false,
+    }
+}
+
+fn update_reticle(
+    targeting: Res<Targeting>,
+    mut reticles: Query<(&mut Transform, &mut Visibility), With<Reticle>>,
+) {
+    if !targeting.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut visibility) in reticles.iter_mut() {
+        let target = targeting.target.filter(|_| targeting.active);
+        place_outline(&mut transform, &mut visibility, target);
+    }
+}
+
+fn update_hover_highlight(
+    hovered: Res<HoveredHex>,
+    mut highlights: Query<(&mut Transform, &mut Visibility), With<HoverHighlight>>,
+) {
+    if !hovered.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut visibility) in highlights.iter_mut() {
+        place_outline(&mut transform, &mut visibility, hovered.0);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::VecDeque, fs, io, path::Path};
+
+use bevy::prelude::*;
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{AiPerceptionPlugin, AiPlugin},
+    domain::{
+        common::{Actor, Facing, HexDirection, HexPos},
+        health::Health,
+    },
+    headless::{headless_app, run_turns, HeadlessPlugin},
+    rng::GameRng,
+    turn_engine::{
+        actions::{ActionQueue, AnyAction},
+        journal::{ActionJournal, JournalEntry},
+        TurnState, TurnSystems,
+    },
+    RunParams, SimulationPlugins,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ActorState {
+    entity: u64,
+    coord: Coordinate,
+    facing: HexDirection,
+    actions_remaining: u8,
+    health: Option<u8>,
+}
+
+// a summary of the world used to check that a replay ended up where the recording did
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorldFingerprint(Vec<ActorState>);
+
+impl WorldFingerprint {
+    pub fn capture(world: &mut World) -> WorldFingerprint {
+        let mut actors: Vec<_> = world
+            .query::<(Entity, &HexPos, &Facing, &Actor, Option<&Health>)>()
+            .iter(world)
+            .map(|(entity, pos, facing, actor, health)| ActorState {
+                entity: entity.to_bits(),
+                coord: pos.0,
+                facing: facing.0,
+                actions_remaining: actor.actions_remaining,
+                health: health.map(|h| h.current),
+            })
+            .collect();
+        actors.sort_by_key(|a| a.entity);
+        WorldFingerprint(actors)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recording {
+    pub params: RunParams,
+    pub journal: ActionJournal,
+    pub fingerprint: WorldFingerprint,
+}
+
+impl Recording {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Recording> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+// runs a headless simulation and keeps everything needed to replay it
+pub fn record_headless(mut params: RunParams, max_turns: u32) -> (App, Recording) {
+    let mut app = headless_app(&params);
+    app.init_resource::<ActionJournal>();
+    params.seed = app.world.get_resource::<GameRng>().map(|rng| rng.seed());
+
+    run_turns(&mut app, max_turns);
+
+    let journal = app.world.get_resource::<ActionJournal>().unwrap().clone();
+    let fingerprint = WorldFingerprint::capture(&mut app.world);
+
+    (
+        app,
+        Recording {
+            params,
+            journal,
+            fingerprint,
+        },
+    )
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Decode(serde_json::Error),
+    Diverged {
+        index: usize,
+        expected: JournalEntry,
+        actual: Option<JournalEntry>,
+    },
+    FinalState {
+        expected: WorldFingerprint,
+        actual: WorldFingerprint,
+    },
+}
+
+struct ReplayQueue(VecDeque<AnyAction>);
+
+// feeds recorded actions to the turn engine one at a time, in place of the AI and player input
+fn feed_replay(
+    turn_state: Res<TurnState>,
+    replay: Option<ResMut<ReplayQueue>>,
+    mut actions: ResMut<ActionQueue>,
+) {
+    if let Some(mut replay) = replay {
+        if actions.is_empty() && matches!(*turn_state, TurnState::Idle) {
+            if let Some(action) = replay.0.pop_front() {
+                actions.push_any(action);
+            }
+        }
+    }
+}
+
+fn replay_finished(world: &World) -> bool {
+    let replay_empty = world
+        .get_resource::<ReplayQueue>()
+        .map_or(true, |replay| replay.0.is_empty());
+    let actions_empty = world
+        .get_resource::<ActionQueue>()
+        .map_or(true, |actions| actions.is_empty());
+    let idle = matches!(world.get_resource::<TurnState>(), Some(TurnState::Idle));
+
+    replay_empty && actions_empty && idle
+}
+
+// rebuilds the recorded start state from its seed, feeds the journal back in and verifies the outcome
+pub fn replay_headless(recording: &Recording) -> Result<App, ReplayError> {
+    let mut app = App::new();
+
+    app.add_plugins_with(SimulationPlugins, |group| group.disable::<AiPlugin>())
+        .add_plugin(AiPerceptionPlugin)
+        .add_plugin(HeadlessPlugin)
+        .init_resource::<ActionJournal>()
+        .add_system(feed_replay);
+
+    recording.params.configure(&mut app);
+
+    // the first update runs the startup systems, which register the action codecs
+    app.update();
+
+    let actions = {
+        let systems = app.world.get_resource::<TurnSystems>().unwrap();
+        recording
+            .journal
+            .entries()
+            .iter()
+            .map(|entry| systems.decode_action(&entry.action))
+            .collect::<Result<VecDeque<_>, _>>()
+            .map_err(ReplayError::Decode)?
+    };
+    app.insert_resource(ReplayQueue(actions));
+
+    while !replay_finished(&app.world) {
+        app.update();
+    }
+
+    {
+        let replayed = app.world.get_resource::<ActionJournal>().unwrap();
+        for (index, expected) in recording.journal.entries().iter().enumerate() {
+            let actual = replayed.entries().get(index);
+            if actual != Some(expected) {
+                return Err(ReplayError::Diverged {
+                    index,
+                    expected: expected.clone(),
+                    actual: actual.cloned(),
+                });
+            }
+        }
+    }
+
+    let actual = WorldFingerprint::capture(&mut app.world);
+    if actual != recording.fingerprint {
+        return Err(ReplayError::FinalState {
+            expected: recording.fingerprint.clone(),
+            actual,
+        });
+    }
+
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{headless::run_headless, Scenario};
+
+    use super::*;
+
+    #[test]
+    fn recording_replays_to_same_world() {
+        let params = RunParams {
+            scenario: Scenario::Cave,
+            seed: None,
+        };
+        let (_, recording) = record_headless(params, 40);
+
+        assert!(!recording.journal.is_empty());
+        replay_headless(&recording).unwrap();
+    }
+
+    #[test]
+    fn only_recorded_runs_keep_a_journal() {
+        let params = RunParams {
+            scenario: Scenario::Arena,
+            seed: Some(5),
+        };
+        let app = run_headless(params, 5);
+
+        assert!(app.world.get_resource::<ActionJournal>().is_none());
+    }
+
+    #[test]
+    fn recording_replays_after_serialization() {
+        let params = RunParams {
+            scenario: Scenario::Arena,
+            seed: Some(5),
+        };
+        let (_, recording) = record_headless(params, 20);
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let recording: Recording = serde_json::from_str(&json).unwrap();
+
+        replay_headless(&recording).unwrap();
+    }
+}
@@ -0,0 +1,415 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, utils::HashMap};
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{
+        ai_hearing::HeardNoise,
+        ai_vision::LastSeen,
+        awareness::Awareness,
+        behaviour_tree::{Blackboard, BtNode},
+        AIBehaviour,
+    },
+    domain::{
+        combat::{Ammo, RangedWeapon},
+        common::{Actor, Facing, HexPos},
+        health::{Armor, Health, Resistances},
+        hearing::Hearing,
+        light::LightSource,
+        turn_queue::TurnQueue,
+        vision::Vision,
+    },
+    intention::PlayerControlled,
+    map::{MapRoot, MapTile, Terrain},
+    render::player_vision::PlayerVisibility,
+    spawn::spawn_tiles,
+    turn_engine::{actions::ActionQueue, TurnState},
+    Player,
+};
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(quicksave.exclusive_system())
+            .add_system(quickload);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedTile {
+    pub coord: Coordinate,
+    pub terrain: Terrain,
+    pub visibility: Option<PlayerVisibility>,
+    #[serde(default)]
+    pub light: Option<LightSource>,
+}
+
+// an AI actor's behaviour tree along with what it remembers
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedBehaviour {
+    pub tree: BtNode,
+    #[serde(default)]
+    pub target: Option<u64>,
+    #[serde(default)]
+    pub home: Option<Coordinate>,
+}
+
+impl SavedBehaviour {
+    fn capture(behaviour: &AIBehaviour, blackboard: Option<&Blackboard>) -> SavedBehaviour {
+        let blackboard = blackboard.cloned().unwrap_or_default();
+        SavedBehaviour {
+            tree: behaviour.0.clone(),
+            target: blackboard.target.map(|e| e.to_bits()),
+            home: blackboard.home,
+        }
+    }
+
+    fn resolve(&self, resolve: impl Fn(u64) -> Option<Entity>) -> (AIBehaviour, Blackboard) {
+        let blackboard = Blackboard {
+            target: self.target.and_then(resolve),
+            home: self.home,
+        };
+        (AIBehaviour(self.tree.clone()), blackboard)
+    }
+}
+
+// every component of an actor which is needed to bring it back; `id` is the entity it was saved from
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedActor {
+    pub id: u64,
+    pub pos: HexPos,
+    pub facing: Facing,
+    pub actor: Actor,
+    pub vision: Option<Vision>,
+    pub ai: Option<SavedBehaviour>,
+    pub player: bool,
+    pub player_controlled: bool,
+    pub player_visibility: Option<PlayerVisibility>,
+    #[serde(default)]
+    pub health: Option<Health>,
+    #[serde(default)]
+    pub armor: Option<Armor>,
+    #[serde(default)]
+    pub resistances: Option<Resistances>,
+    #[serde(default)]
+    pub weapon: Option<RangedWeapon>,
+    #[serde(default)]
+    pub ammo: Option<Ammo>,
+    #[serde(default)]
+    pub last_seen: Option<LastSeen>,
+    #[serde(default)]
+    pub hearing: Option<Hearing>,
+    #[serde(default)]
+    pub heard_noise: Option<HeardNoise>,
+    #[serde(default)]
+    pub awareness: Option<Awareness>,
+    #[serde(default)]
+    pub light: Option<LightSource>,
+}
+
+impl SavedActor {
+    pub fn capture(world: &World, entity: Entity) -> Option<SavedActor> {
+        let e = world.get_entity(entity)?;
+
+        Some(SavedActor {
+            id: entity.to_bits(),
+            pos: e.get::<HexPos>()?.clone(),
+            facing: *e.get::<Facing>()?,
+            actor: e.get::<Actor>()?.clone(),
+            vision: e.get::<Vision>().cloned(),
+            ai: e
+                .get::<AIBehaviour>()
+                .map(|ai| SavedBehaviour::capture(ai, e.get::<Blackboard>())),
+            player: e.contains::<Player>(),
+            player_controlled: e.contains::<PlayerControlled>(),
+            player_visibility: e.get::<PlayerVisibility>().cloned(),
+            health: e.get::<Health>().cloned(),
+            armor: e.get::<Armor>().copied(),
+            resistances: e.get::<Resistances>().cloned(),
+            weapon: e.get::<RangedWeapon>().copied(),
+            ammo: e.get::<Ammo>().copied(),
+            last_seen: e.get::<LastSeen>().copied(),
+            hearing: e.get::<Hearing>().copied(),
+            heard_noise: e.get::<HeardNoise>().copied(),
+            awareness: e.get::<Awareness>().copied(),
+            light: e.get::<LightSource>().copied(),
+        })
+    }
+
+    // `resolve` maps saved entity ids to the entities they have been restored as
+    pub fn insert_into(
+        &self,
+        entity: &mut EntityCommands,
+        resolve: impl Fn(u64) -> Option<Entity>,
+    ) {
+        entity.insert_bundle((self.pos.clone(), self.facing, self.actor.clone()));
+
+        if let Some(vision) = &self.vision {
+            entity.insert(vision.clone());
+        }
+        if let Some(ai) = &self.ai {
+            entity.insert_bundle(ai.resolve(resolve));
+        }
+        if self.player {
+            entity.insert(Player);
+        }
+        if self.player_controlled {
+            entity.insert(PlayerControlled);
+        }
+        if let Some(player_vis) = &self.player_visibility {
+            entity.insert(player_vis.clone());
+        }
+        if let Some(health) = &self.health {
+            entity.insert(health.clone());
+        }
+        if let Some(armor) = self.armor {
+            entity.insert(armor);
+        }
+        if let Some(resistances) = &self.resistances {
+            entity.insert(resistances.clone());
+        }
+        if let Some(weapon) = self.weapon {
+            entity.insert(weapon);
+        }
+        if let Some(ammo) = self.ammo {
+            entity.insert(ammo);
+        }
+        if let Some(last_seen) = self.last_seen {
+            entity.insert(last_seen);
+        }
+        if let Some(hearing) = self.hearing {
+            entity.insert(hearing);
+        }
+        if let Some(heard_noise) = self.heard_noise {
+            entity.insert(heard_noise);
+        }
+        if let Some(awareness) = self.awareness {
+            entity.insert(awareness);
+        }
+        if let Some(light) = self.light {
+            entity.insert(light);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveGame {
+    pub tiles: Vec<SavedTile>,
+    pub actors: Vec<SavedActor>,
+    pub turn_order: Vec<u64>,
+    pub turns_elapsed: u32,
+}
+
+impl SaveGame {
+    pub fn capture(world: &mut World) -> SaveGame {
+        let mut tiles: Vec<_> = world
+            .query::<(
+                &HexPos,
+                &MapTile,
+                Option<&PlayerVisibility>,
+                Option<&LightSource>,
+            )>()
+            .iter(world)
+            .map(|(pos, tile, vis, light)| SavedTile {
+                coord: pos.0,
+                terrain: tile.terrain,
+                visibility: vis.cloned(),
+                light: light.copied(),
+            })
+            .collect();
+        tiles.sort_by_key(|t| (t.coord.x, t.coord.y));
+
+        let mut entities: Vec<Entity> = world
+            .query_filtered::<Entity, With<Actor>>()
+            .iter(world)
+            .collect();
+        entities.sort_by_key(|e| e.to_bits());
+
+        let actors = entities
+            .into_iter()
+            .filter_map(|e| SavedActor::capture(world, e))
+            .collect();
+
+        let turn_queue = world.get_resource::<TurnQueue>().unwrap();
+
+        SaveGame {
+            tiles,
+            actors,
+            turn_order: turn_queue.iter().map(|e| e.to_bits()).collect(),
+            turns_elapsed: turn_queue.turns_elapsed(),
+        }
+    }
+
+    pub fn spawn(&self, commands: &mut Commands, turn_queue: &mut TurnQueue) {
+        spawn_tiles(
+            commands,
+            self.tiles.iter().map(|t| {
+                (
+                    t.coord,
+                    MapTile { terrain: t.terrain },
+                    t.visibility
+                        .clone()
+                        .unwrap_or_else(PlayerVisibility::new_persistent),
+                    t.light,
+                )
+            }),
+        );
+
+        let entities: HashMap<u64, Entity> = self
+            .actors
+            .iter()
+            .map(|a| (a.id, commands.spawn().id()))
+            .collect();
+
+        for actor in self.actors.iter() {
+            actor.insert_into(&mut commands.entity(entities[&actor.id]), |id| {
+                entities.get(&id).copied()
+            });
+        }
+
+        turn_queue.restore(
+            self.turn_order
+                .iter()
+                .filter_map(|id| entities.get(id).copied()),
+            self.turns_elapsed,
+        );
+    }
+
+    pub fn store(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        storage::write(&json)
+    }
+
+    pub fn load() -> Result<SaveGame, String> {
+        let json = storage::read()?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    const SAVE_PATH: &str = "savegame.json";
+
+    pub fn write(json: &str) -> Result<(), String> {
+        std::fs::write(SAVE_PATH, json).map_err(|e| e.to_string())
+    }
+
+    pub fn read() -> Result<String, String> {
+        std::fs::read_to_string(SAVE_PATH).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const SAVE_KEY: &str = "beverage-save";
+
+    fn local_storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .ok_or("No window")?
+            .local_storage()
+            .map_err(|e| format!("{e:?}"))?
+            .ok_or_else(|| "No local storage".to_string())
+    }
+
+    pub fn write(json: &str) -> Result<(), String> {
+        local_storage()?
+            .set_item(SAVE_KEY, json)
+            .map_err(|e| format!("{e:?}"))
+    }
+
+    pub fn read() -> Result<String, String> {
+        local_storage()?
+            .get_item(SAVE_KEY)
+            .map_err(|e| format!("{e:?}"))?
+            .ok_or_else(|| "No saved game".to_string())
+    }
+}
+
+fn quicksave(world: &mut World) {
+    let pressed = world
+        .get_resource::<Input<KeyCode>>()
+        .map_or(false, |keys| keys.just_pressed(KeyCode::F5));
+
+    if pressed {
+        match SaveGame::capture(world).store() {
+            Ok(()) => info!("Game saved"),
+            Err(e) => eprintln!("Could not save game: {e}"),
+        }
+    }
+}
+
+fn quickload(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    turn_state: Res<TurnState>,
+    roots: Query<Entity, With<MapRoot>>,
+    actors: Query<Entity, With<Actor>>,
+    mut turn_queue: ResMut<TurnQueue>,
+    mut actions: ResMut<ActionQueue>,
+) {
+    if !keys.just_pressed(KeyCode::F9) || !matches!(*turn_state, TurnState::Idle) {
+        return;
+    }
+
+    match SaveGame::load() {
+        Ok(save) => {
+            for e in roots.iter().chain(actors.iter()) {
+                commands.entity(e).despawn_recursive();
+            }
+            actions.clear();
+            save.spawn(&mut commands, &mut turn_queue);
+        }
+        Err(e) => eprintln!("Could not load game: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use crate::{headless::run_headless, RunParams, Scenario};
+
+    use super::*;
+
+    fn restore(save: &SaveGame) -> World {
+        let mut world = World::new();
+        let mut turn_queue = TurnQueue::default();
+        let mut queue = CommandQueue::default();
+
+        {
+            let mut commands = Commands::new(&mut queue, &world);
+            save.spawn(&mut commands, &mut turn_queue);
+        }
+        queue.apply(&mut world);
+
+        world.insert_resource(turn_queue);
+        world
+    }
+
+    fn positions(save: &SaveGame) -> Vec<(HexPos, i32)> {
+        let by_id: HashMap<u64, &SavedActor> = save.actors.iter().map(|a| (a.id, a)).collect();
+        save.turn_order
+            .iter()
+            .map(|id| (by_id[id].pos.clone(), by_id[id].facing.0 as i32))
+            .collect()
+    }
+
+    #[test]
+    fn saved_game_restores_world_and_turn_order() {
+        let params = RunParams {
+            scenario: Scenario::Cave,
+            seed: Some(3),
+        };
+        let mut app = run_headless(params, 10);
+        let save = SaveGame::capture(&mut app.world);
+
+        let json = serde_json::to_string(&save).unwrap();
+        let loaded: SaveGame = serde_json::from_str(&json).unwrap();
+        let restored = SaveGame::capture(&mut restore(&loaded));
+
+        assert_eq!(restored.tiles.len(), save.tiles.len());
+        assert_eq!(restored.turns_elapsed, save.turns_elapsed);
+        assert_eq!(positions(&restored), positions(&save));
+    }
+}
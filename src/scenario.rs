@@ -3,6 +3,8 @@ use bevy::prelude::*;
 use crate::{
     domain::turn_queue::TurnQueue,
     map::{BasicHex, DrunkardsWalk, MapGenerator},
+    rng::GameRng,
+    save::SaveGame,
     spawn::spawn_map_entities,
 };
 
@@ -13,10 +15,10 @@ impl Plugin for CaveScenario {
     }
 }
 impl CaveScenario {
-    fn setup(mut commands: Commands, mut turn_queue: ResMut<TurnQueue>) {
-        let map = DrunkardsWalk::example().generate_map();
+    fn setup(mut commands: Commands, mut turn_queue: ResMut<TurnQueue>, mut rng: ResMut<GameRng>) {
+        let map = DrunkardsWalk::example().generate_map(&mut *rng);
 
-        spawn_map_entities(&mut commands, &mut turn_queue, &map);
+        spawn_map_entities(&mut commands, &mut turn_queue, &map, &mut *rng);
     }
 }
 
@@ -27,9 +29,30 @@ impl Plugin for ArenaScenario {
     }
 }
 impl ArenaScenario {
-    fn setup(mut commands: Commands, mut turn_queue: ResMut<TurnQueue>) {
-        let map = BasicHex::new(5).generate_map();
+    fn setup(mut commands: Commands, mut turn_queue: ResMut<TurnQueue>, mut rng: ResMut<GameRng>) {
+        let map = BasicHex::new(5).generate_map(&mut *rng);
 
-        spawn_map_entities(&mut commands, &mut turn_queue, &map);
+        spawn_map_entities(&mut commands, &mut turn_queue, &map, &mut *rng);
+    }
+}
+
+// restores the last saved game, falling back to the arena if there is none
+pub struct SavedScenario;
+impl Plugin for SavedScenario {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(Self::setup);
+    }
+}
+impl SavedScenario {
+    fn setup(mut commands: Commands, mut turn_queue: ResMut<TurnQueue>, mut rng: ResMut<GameRng>) {
+        match SaveGame::load() {
+            Ok(save) => save.spawn(&mut commands, &mut turn_queue),
+            Err(e) => {
+                eprintln!("Could not load saved game: {e}");
+                let map = BasicHex::new(5).generate_map(&mut *rng);
+
+                spawn_map_entities(&mut commands, &mut turn_queue, &map, &mut *rng);
+            }
+        }
     }
 }
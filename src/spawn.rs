@@ -1,5 +1,12 @@
+use crate::ai::awareness::Awareness;
+use crate::ai::behaviour_tree::Blackboard;
 use crate::ai::*;
+use crate::domain::combat::{Ammo, RangedWeapon};
 use crate::domain::common::*;
+use crate::domain::health::Health;
+use crate::domain::hearing::Hearing;
+use crate::domain::light::LightSource;
+use crate::domain::los::LosRule;
 use crate::domain::turn_queue::TurnQueue;
 use crate::domain::vision::Vision;
 use crate::domain::vision::VisionType;
@@ -7,26 +14,23 @@ use crate::intention::PlayerControlled;
 use crate::map::*;
 use crate::maths::RADIANS_120DEG;
 use crate::maths::RADIANS_60DEG;
-use crate::render::actor::render_enemy;
-use crate::render::actor::render_player;
-use crate::render::map::tile_render_bundle;
 use crate::render::player_vision::PlayerVisibility;
 use crate::Player;
-use bevy::prelude::*;
-use bevy_prototype_lyon::entity::ShapeBundle;
+use bevy::{prelude::*, utils::HashSet};
 use hex2d::*;
 use rand::prelude::*;
 
 #[derive(Bundle)]
 struct ActorBundle {
-    #[bundle]
-    shape: ShapeBundle,
-
     facing: Facing,
     pos: HexPos,
     actor: Actor,
+    health: Health,
 }
 
+// a fire on the floor which lights the area around it
+const BRAZIER: LightSource = LightSource { radius: 3 };
+
 #[derive(Bundle)]
 struct PlayerBundle {
     #[bundle]
@@ -35,6 +39,8 @@ struct PlayerBundle {
     vision: Vision,
     player_controlled: PlayerControlled,
     player: Player,
+    weapon: RangedWeapon,
+    ammo: Ammo,
 }
 
 #[derive(Bundle)]
@@ -43,15 +49,15 @@ struct AiBundle {
     actor: ActorBundle,
 
     vision: Vision,
+    hearing: Hearing,
+    awareness: Awareness,
     ai: AIBehaviour,
+    blackboard: Blackboard,
     player_vis: PlayerVisibility,
 }
 
 #[derive(Bundle)]
 struct MapTileBundle {
-    #[bundle]
-    shape: ShapeBundle,
-
     pos: HexPos,
     tile: MapTile,
     player_vis: PlayerVisibility,
@@ -61,40 +67,69 @@ pub fn spawn_map_entities(
     commands: &mut Commands,
     turn_queue: &mut TurnQueue,
     map: &Map,
+    rng: &mut impl Rng,
 ) -> Entity {
-    let map_entity = commands
-        .spawn()
-        .with_children(|parent| {
-            for (&c, cell) in map.cells.iter() {
-                let tile = MapTile {
+    let braziers: HashSet<Coordinate> = floor_coordinates(&map.cells)
+        .choose_multiple(rng, 2)
+        .copied()
+        .collect();
+
+    let map_entity = spawn_tiles(
+        commands,
+        map.cells.iter().map(|(&c, cell)| {
+            (
+                c,
+                MapTile {
                     terrain: cell.terrain,
-                };
-                let shape = tile_render_bundle(c, &tile);
-                parent.spawn_bundle(MapTileBundle {
-                    shape,
-                    pos: HexPos(c),
-                    tile,
-                    player_vis: PlayerVisibility::new_persistent(),
-                });
-            }
-        })
-        .id();
+                },
+                PlayerVisibility::new_persistent(),
+                braziers.contains(&c).then(|| BRAZIER),
+            )
+        }),
+    );
 
     spawn_player(commands, turn_queue, map.player_start);
 
-    for c in map
-        .cells
-        .iter()
-        .filter(|(c, cell)| c.distance(map.player_start) > 2 && cell.terrain == Terrain::Floor)
-        .map(|(c, _)| c)
-        .choose_multiple(&mut thread_rng(), 3)
-    {
-        spawn_enemy(commands, turn_queue, *c, AIBehaviour::Wandering);
+    let enemy_starts: Vec<_> = floor_coordinates(&map.cells)
+        .into_iter()
+        .filter(|c| c.distance(map.player_start) > 2)
+        .collect();
+
+    // one of the enemies keeps watch over where it starts rather than roaming
+    for (i, &c) in enemy_starts.choose_multiple(rng, 3).enumerate() {
+        let ai = if i == 0 {
+            AIBehaviour::sentry()
+        } else {
+            AIBehaviour::hunter()
+        };
+        spawn_enemy(commands, turn_queue, c, ai, rng);
     }
 
     map_entity
 }
 
+pub fn spawn_tiles(
+    commands: &mut Commands,
+    tiles: impl IntoIterator<Item = (Coordinate, MapTile, PlayerVisibility, Option<LightSource>)>,
+) -> Entity {
+    commands
+        .spawn()
+        .insert(MapRoot)
+        .with_children(|parent| {
+            for (c, tile, player_vis, light) in tiles {
+                let mut tile = parent.spawn_bundle(MapTileBundle {
+                    pos: HexPos(c),
+                    tile,
+                    player_vis,
+                });
+                if let Some(light) = light {
+                    tile.insert(light);
+                }
+            }
+        })
+        .id()
+}
+
 pub fn spawn_player(
     commands: &mut Commands,
     turn_queue: &mut TurnQueue,
@@ -110,7 +145,6 @@ pub fn spawn_player(
 fn new_player(coord: Coordinate) -> PlayerBundle {
     let facing = Facing::default();
     let pos = HexPos(coord);
-    let shape = render_player(&pos, &facing);
 
     let actor = Actor {
         actions_per_turn: 2,
@@ -120,19 +154,25 @@ fn new_player(coord: Coordinate) -> PlayerBundle {
     let vision = VisionType::Radial(5)
         .and(VisionType::Conical(RADIANS_120DEG))
         .or(VisionType::Radial(1))
-        .and(VisionType::Obstructable);
+        .and(VisionType::Obstructable)
+        .and(VisionType::Lit(1));
 
     PlayerBundle {
         actor: ActorBundle {
             facing,
             pos,
-            shape,
             actor,
+            health: Health::new(6),
         },
 
-        vision: Vision::new(vision),
+        vision: Vision::new(vision).with_los(LosRule::Permissive),
         player_controlled: PlayerControlled,
         player: Player,
+        weapon: RangedWeapon {
+            range: 5,
+            damage: 2,
+        },
+        ammo: Ammo::new(3),
     }
 }
 
@@ -141,19 +181,19 @@ pub fn spawn_enemy(
     turn_queue: &mut TurnQueue,
     coordinate: Coordinate,
     ai: AIBehaviour,
+    rng: &mut impl Rng,
 ) -> Entity {
-    let enemy = commands.spawn_bundle(new_enemy(coordinate, ai)).id();
+    let enemy = commands.spawn_bundle(new_enemy(coordinate, ai, rng)).id();
 
     turn_queue.enqueue(enemy);
 
     enemy
 }
 
-fn new_enemy(coord: Coordinate, ai: AIBehaviour) -> AiBundle {
-    let direction = HexDirection::all().choose(&mut thread_rng()).unwrap();
+fn new_enemy(coord: Coordinate, ai: AIBehaviour, rng: &mut impl Rng) -> AiBundle {
+    let direction = HexDirection::all().choose(rng).unwrap();
     let facing = Facing(*direction);
     let pos = HexPos(coord);
-    let shape = render_enemy(&pos, &facing);
 
     let actor = Actor {
         actions_per_turn: 2,
@@ -163,17 +203,21 @@ fn new_enemy(coord: Coordinate, ai: AIBehaviour) -> AiBundle {
     let vision = VisionType::Radial(5)
         .and(VisionType::Conical(RADIANS_60DEG))
         .or(VisionType::Radial(1))
-        .and(VisionType::Obstructable);
+        .and(VisionType::Obstructable)
+        .and(VisionType::Lit(1));
 
     AiBundle {
         actor: ActorBundle {
             facing,
             pos,
-            shape,
             actor,
+            health: Health::new(3),
         },
-        vision: Vision::new(vision),
+        vision: Vision::new(vision).with_los(LosRule::Permissive),
+        hearing: Hearing::default(),
+        awareness: Awareness::default(),
         ai,
+        blackboard: Blackboard::default(),
         player_vis: PlayerVisibility::new_transient(),
     }
 }
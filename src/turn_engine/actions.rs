@@ -1,6 +1,8 @@
 use std::any::TypeId;
 use std::collections::VecDeque;
+use std::fmt;
 
+use bevy::prelude::Entity;
 use bevy_ecs::query::QueryEntityError;
 use downcast_rs::*;
 use dyn_clone::DynClone;
@@ -8,7 +10,10 @@ use dyn_clone::DynClone;
 use super::{effects::EffectQueue, DynamicWrapper, InnerType};
 
 pub trait Action: Downcast + DynClone + Send + Sync + std::fmt::Debug {
+    fn entity(&self) -> Entity;
     fn cost(&self) -> u8;
+    // a short name for the action, as shown to the player
+    fn describe(&self) -> String;
 }
 impl_downcast!(Action);
 dyn_clone::clone_trait_object!(Action);
@@ -17,9 +22,21 @@ dyn_clone::clone_trait_object!(Action);
 pub struct AnyAction(pub Box<dyn Action>);
 
 impl AnyAction {
+    pub fn entity(&self) -> Entity {
+        self.0.entity()
+    }
+
     pub fn cost(&self) -> u8 {
         self.0.cost()
     }
+
+    pub fn describe(&self) -> String {
+        self.0.describe()
+    }
+
+    pub fn is<A: Action>(&self) -> bool {
+        self.0.is::<A>()
+    }
 }
 
 impl<A: Action> From<A> for AnyAction {
@@ -80,19 +97,72 @@ impl ActionQueue {
         cost
     }
 
+    pub fn push_any(&mut self, action: AnyAction) -> u8 {
+        let cost = action.cost();
+        self.0.push_back(action);
+        cost
+    }
+
+    pub fn append(&mut self, mut other: ActionQueue) {
+        self.0.append(&mut other.0);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    // drops everything queued after the first `len` actions
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AnyAction> {
+        self.0.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl IntoIterator for ActionQueue {
+    type Item = AnyAction;
+    type IntoIter = std::collections::vec_deque::IntoIter<AnyAction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
 }
 
 pub type ActionResult = Result<EffectQueue, AnyActionError>;
 
-pub trait ActionError: core::fmt::Debug {}
+pub trait ActionError: Downcast + Send + Sync + fmt::Debug + fmt::Display {}
+impl_downcast!(ActionError);
 
 #[derive(Debug)]
 pub struct AnyActionError(Box<dyn ActionError>);
 
+impl<E: ActionError> From<E> for AnyActionError {
+    fn from(error: E) -> Self {
+        AnyActionError(Box::new(error))
+    }
+}
+
+impl fmt::Display for AnyActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl AnyActionError {
+    pub fn downcast_ref<E: ActionError>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+
     pub fn res_generic<T>(str: &str) -> Result<T, AnyActionError> {
         Err(Self::generic(str))
     }
@@ -106,12 +176,24 @@ impl AnyActionError {
 pub struct GenericActionError(String);
 impl ActionError for GenericActionError {}
 
+impl fmt::Display for GenericActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct QueryEntityActionError(QueryEntityError);
 impl ActionError for QueryEntityActionError {}
 
+impl fmt::Display for QueryEntityActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
 impl From<QueryEntityError> for AnyActionError {
     fn from(e: QueryEntityError) -> Self {
-        AnyActionError(Box::new(QueryEntityActionError(e)))
+        QueryEntityActionError(e).into()
     }
 }
@@ -1,18 +1,31 @@
 use std::any::TypeId;
 use std::collections::VecDeque;
 
+use bevy::prelude::World;
 use downcast_rs::*;
 use dyn_clone::*;
 
 use super::{DynamicWrapper, InnerType};
 
-pub trait Effect: Downcast + DynClone + Send + Sync + std::fmt::Debug {}
+pub trait Effect: Downcast + DynClone + Send + Sync + std::fmt::Debug {
+    // the effect which restores the world to how it was before this one is applied;
+    // `None` means the effect cannot be undone
+    fn inverse(&self, _world: &World) -> Option<AnyEffect> {
+        None
+    }
+}
 downcast_rs::impl_downcast!(Effect);
 dyn_clone::clone_trait_object!(Effect);
 
 #[derive(Debug, Clone)]
 pub struct AnyEffect(pub Box<dyn Effect>);
 
+impl AnyEffect {
+    pub fn inverse(&self, world: &World) -> Option<AnyEffect> {
+        self.0.inverse(world)
+    }
+}
+
 impl<E: Effect> From<E> for AnyEffect {
     fn from(effect: E) -> Self {
         AnyEffect(Box::new(effect))
@@ -36,7 +49,7 @@ impl InnerType for AnyEffect {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct EffectQueue(VecDeque<AnyEffect>);
 
 impl<E, const N: usize> From<[E; N]> for EffectQueue
@@ -76,10 +89,29 @@ impl EffectQueue {
         self
     }
 
+    pub fn push_front<T: Into<AnyEffect>>(&mut self, effect: T) {
+        self.0.push_front(effect.into());
+    }
+
+    // puts `other` at the front of the queue, keeping its order
+    pub fn prepend(&mut self, other: EffectQueue) {
+        for effect in other.0.into_iter().rev() {
+            self.0.push_front(effect);
+        }
+    }
+
     pub fn append(&mut self, mut other: EffectQueue) {
         self.0.append(&mut other.0);
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AnyEffect> {
+        self.0.iter()
+    }
+
     pub fn find<E: Effect>(&self) -> Option<&E> {
         self.0.iter().find_map(|e| e.downcast_ref())
     }
@@ -0,0 +1,11 @@
+// (de)serializes an Entity by its bits, for use with `#[serde(with = "...")]`
+use bevy::prelude::Entity;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(entity.to_bits())
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+    u64::deserialize(deserializer).map(Entity::from_bits)
+}
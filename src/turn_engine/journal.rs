@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// an action or effect tagged with the name of its concrete type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Encoded {
+    pub kind: String,
+    pub data: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Accepted { effects: Vec<Encoded> },
+    Rejected { error: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalEntry {
+    pub action: Encoded,
+    pub outcome: Outcome,
+}
+
+// every action taken by the ActionExecutor, in the order it was handled
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ActionJournal {
+    entries: Vec<JournalEntry>,
+}
+
+impl ActionJournal {
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -1,15 +1,24 @@
 use std::any::TypeId;
 
 use bevy::{prelude::*, utils::HashMap};
-use bevy_ecs::archetype::ArchetypeGeneration;
+use bevy_ecs::{archetype::ArchetypeGeneration, query::Access};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 
 use self::{
-    actions::{Action, ActionQueue, ActionResult, AnyAction},
+    actions::{Action, ActionQueue, ActionResult, AnyAction, AnyActionError},
     effects::{AnyEffect, Effect, EffectQueue},
+    journal::{ActionJournal, Encoded, JournalEntry, Outcome},
+    reactions::Reaction,
+    undo::{RedoAction, UndoAction, UndoHistory},
 };
 
 pub mod actions;
 pub mod effects;
+pub mod entity_serde;
+pub mod journal;
+pub mod reactions;
+pub mod undo;
 
 struct TypedSystemRunner<In, Out, S>
 where
@@ -18,6 +27,8 @@ where
     system: S,
     initialized: bool,
     archetype_generation: ArchetypeGeneration,
+    // read only systems may not write to the world, and anything they buffer is never applied
+    read_only: bool,
 }
 
 impl<In, Out, S> TypedSystemRunner<In, Out, S>
@@ -29,6 +40,14 @@ where
             system,
             initialized: false,
             archetype_generation: ArchetypeGeneration::initial(),
+            read_only: false,
+        }
+    }
+
+    pub fn read_only(system: S) -> TypedSystemRunner<In, Out, S> {
+        TypedSystemRunner {
+            read_only: true,
+            ..TypedSystemRunner::new(system)
         }
     }
 }
@@ -45,6 +64,17 @@ trait InnerType {
     fn inner_type(&self) -> TypeId;
 }
 
+// generators take the entity they generate actions for as-is
+impl DynamicWrapper<Entity> for Entity {
+    fn downcast(self) -> Option<Entity> {
+        Some(self)
+    }
+
+    fn downcast_ref(&self) -> Option<&Entity> {
+        Some(self)
+    }
+}
+
 impl<InDyn, In, Out, S> AnyRunner<InDyn, Out> for TypedSystemRunner<In, Out, S>
 where
     InDyn: DynamicWrapper<In>,
@@ -55,6 +85,18 @@ where
             if !self.initialized {
                 self.system.initialize(world);
                 self.initialized = true;
+
+                if self.read_only {
+                    let mut reads_everything = Access::default();
+                    reads_everything.read_all();
+                    assert!(
+                        self.system
+                            .component_access()
+                            .is_compatible(&reads_everything),
+                        "{} may only read the world",
+                        self.system.name()
+                    );
+                }
             } else {
                 let archetypes = world.archetypes();
                 let new_generation = archetypes.generation();
@@ -74,7 +116,9 @@ where
                 }
             }
             let res = self.system.run(input, world);
-            self.system.apply_buffers(world);
+            if !self.read_only {
+                self.system.apply_buffers(world);
+            }
             res
         } else {
             panic!("AnyRunner downcast failed");
@@ -96,6 +140,18 @@ impl<InDyn, Out: 'static> SystemRegistry<InDyn, Out> {
             Box::new(TypedSystemRunner::new(system.system())),
         );
     }
+
+    pub fn register_read_only_system<In: 'static, Params>(
+        &mut self,
+        system: impl IntoSystem<In, Out, Params>,
+    ) where
+        InDyn: DynamicWrapper<In>,
+    {
+        self.map.insert(
+            TypeId::of::<In>(),
+            Box::new(TypedSystemRunner::read_only(system.system())),
+        );
+    }
 }
 
 impl<InDyn, Out> Default for SystemRegistry<InDyn, Out> {
@@ -117,34 +173,198 @@ impl<InDyn: InnerType, Out> AnyRunner<InDyn, Out> for SystemRegistry<InDyn, Out>
     }
 }
 
+// converts dynamic actions and effects to and from json, keyed by the name of their concrete type
+struct CodecRegistry<Dyn> {
+    encoders: HashMap<TypeId, (&'static str, fn(&Dyn) -> serde_json::Result<Value>)>,
+    decoders: HashMap<&'static str, fn(Value) -> serde_json::Result<Dyn>>,
+}
+
+impl<Dyn: InnerType> CodecRegistry<Dyn> {
+    pub fn register<T>(&mut self)
+    where
+        T: Serialize + DeserializeOwned + 'static,
+        Dyn: DynamicWrapper<T> + From<T>,
+    {
+        let name = std::any::type_name::<T>();
+        self.encoders
+            .insert(TypeId::of::<T>(), (name, encode_dynamic::<T, Dyn>));
+        self.decoders.insert(name, decode_dynamic::<T, Dyn>);
+    }
+
+    pub fn encode(&self, value: &Dyn) -> serde_json::Result<Encoded> {
+        let input_type = value.inner_type();
+        if let Some((kind, encode)) = self.encoders.get(&input_type) {
+            Ok(Encoded {
+                kind: kind.to_string(),
+                data: encode(value)?,
+            })
+        } else {
+            panic!("Could not find codec for {:?}", input_type);
+        }
+    }
+
+    pub fn decode(&self, encoded: &Encoded) -> serde_json::Result<Dyn> {
+        if let Some(decode) = self.decoders.get(encoded.kind.as_str()) {
+            decode(encoded.data.clone())
+        } else {
+            Err(serde::de::Error::custom(format!(
+                "Unknown kind {}",
+                encoded.kind
+            )))
+        }
+    }
+}
+
+impl<Dyn> Default for CodecRegistry<Dyn> {
+    fn default() -> Self {
+        Self {
+            encoders: Default::default(),
+            decoders: Default::default(),
+        }
+    }
+}
+
+fn encode_dynamic<T: Serialize, Dyn: DynamicWrapper<T>>(value: &Dyn) -> serde_json::Result<Value> {
+    serde_json::to_value(value.downcast_ref().expect("Codec downcast failed"))
+}
+
+fn decode_dynamic<T: DeserializeOwned, Dyn: From<T>>(value: Value) -> serde_json::Result<Dyn> {
+    serde_json::from_value::<T>(value).map(Dyn::from)
+}
+
 #[derive(Default)]
 pub struct TurnSystems {
     effects: SystemRegistry<AnyEffect>,
     actions: SystemRegistry<AnyAction, ActionResult>,
+    // kept in registration order so that candidate actions are always listed the same way
+    generators: Vec<Box<dyn AnyRunner<Entity, ActionQueue>>>,
+    reactions: HashMap<TypeId, Vec<Box<dyn AnyRunner<AnyEffect, Reaction>>>>,
+    effect_codecs: CodecRegistry<AnyEffect>,
+    action_codecs: CodecRegistry<AnyAction>,
 }
 
 impl TurnSystems {
-    pub fn register_action_handler<A: Action + 'static, Params>(
+    // handlers describe what an action would do through the effects they return, without changing
+    // anything themselves, as they are also run to preview actions which are never taken
+    pub fn register_action_handler<A, Params>(
         &mut self,
         system: impl IntoSystem<A, ActionResult, Params>,
+    ) where
+        A: Action + Serialize + DeserializeOwned + 'static,
+    {
+        self.actions.register_read_only_system(system);
+        self.action_codecs.register::<A>();
+    }
+
+    // a generator lists every action of one kind which an entity might attempt
+    pub fn register_action_generator<Params>(
+        &mut self,
+        system: impl IntoSystem<Entity, ActionQueue, Params>,
     ) {
-        self.actions.register_system(system);
+        self.generators
+            .push(Box::new(TypedSystemRunner::new(system.system())));
+    }
+
+    pub fn generate_actions(&mut self, entity: Entity, world: &mut World) -> ActionQueue {
+        let mut candidates = ActionQueue::default();
+        for generator in self.generators.iter_mut() {
+            candidates.append(generator.run(entity, world));
+        }
+        candidates
+    }
+
+    // every candidate action which would currently be accepted, along with its effects
+    pub fn legal_actions(
+        &mut self,
+        entity: Entity,
+        world: &mut World,
+    ) -> Vec<(AnyAction, EffectQueue)> {
+        self.generate_actions(entity, world)
+            .into_iter()
+            .filter_map(|action| {
+                let effects = self.validate_action(&action, world).ok()?;
+                Some((action, effects))
+            })
+            .collect()
     }
 
     pub fn run_action_system(&mut self, action: AnyAction, world: &mut World) -> ActionResult {
         self.actions.run(action, world)
     }
 
-    pub fn register_effect_handler<E: Effect + 'static, Params>(
-        &mut self,
-        system: impl IntoSystem<E, (), Params>,
-    ) {
+    // previews the effects an action would have without committing them; handlers can only read
+    // the world, so this is safe to call for actions which will never be taken
+    pub fn validate_action(&mut self, action: &AnyAction, world: &mut World) -> ActionResult {
+        self.run_action_system(action.clone(), world)
+    }
+
+    pub fn register_effect_handler<E, Params>(&mut self, system: impl IntoSystem<E, (), Params>)
+    where
+        E: Effect + Serialize + DeserializeOwned + 'static,
+    {
         self.effects.register_system(system);
+        self.effect_codecs.register::<E>();
     }
 
     pub fn run_effect_system(&mut self, effect: AnyEffect, world: &mut World) {
         self.effects.run(effect, world)
     }
+
+    // a reaction observes every effect of type `E` just before it applies
+    pub fn register_reaction<E, Params>(&mut self, system: impl IntoSystem<E, Reaction, Params>)
+    where
+        E: Effect + 'static,
+    {
+        self.reactions
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(Box::new(TypedSystemRunner::new(system.system())));
+    }
+
+    pub fn react(&mut self, effect: &AnyEffect, world: &mut World) -> Reaction {
+        let mut combined = Reaction::none();
+        if let Some(reactions) = self.reactions.get_mut(&effect.inner_type()) {
+            for reaction in reactions.iter_mut() {
+                combined.merge(reaction.run(effect.clone(), world));
+            }
+        }
+        combined
+    }
+
+    pub fn encode_action(&self, action: &AnyAction) -> serde_json::Result<Encoded> {
+        self.action_codecs.encode(action)
+    }
+
+    pub fn decode_action(&self, encoded: &Encoded) -> serde_json::Result<AnyAction> {
+        self.action_codecs.decode(encoded)
+    }
+
+    pub fn encode_effect(&self, effect: &AnyEffect) -> serde_json::Result<Encoded> {
+        self.effect_codecs.encode(effect)
+    }
+
+    pub fn decode_effect(&self, encoded: &Encoded) -> serde_json::Result<AnyEffect> {
+        self.effect_codecs.decode(encoded)
+    }
+
+    fn journal_entry(&self, action: &AnyAction, result: &ActionResult) -> JournalEntry {
+        let outcome = match result {
+            Ok(effects) => Outcome::Accepted {
+                effects: effects
+                    .iter()
+                    .map(|e| self.encode_effect(e).expect("Could not encode effect"))
+                    .collect(),
+            },
+            Err(error) => Outcome::Rejected {
+                error: format!("{error:?}"),
+            },
+        };
+
+        JournalEntry {
+            action: self.encode_action(action).expect("Could not encode action"),
+            outcome,
+        }
+    }
 }
 
 pub struct ActionExecutor;
@@ -155,12 +375,24 @@ impl Stage for ActionExecutor {
                 world.resource_scope(|world, mut systems: Mut<TurnSystems>| {
                     let mut action_queue = world.get_resource_mut::<ActionQueue>().unwrap();
                     if let Some(action) = action_queue.pop() {
-                        match systems.run_action_system(action.clone(), world) {
+                        let result = systems.validate_action(&action, world);
+
+                        // only kept while a run is being recorded
+                        if let Some(mut journal) = world.get_resource_mut::<ActionJournal>() {
+                            journal.record(systems.journal_entry(&action, &result));
+                        }
+
+                        match result {
                             Ok(effects) => {
                                 *state = TurnState::Executing { action, effects };
                             }
-                            Err(message) => {
-                                eprintln!("Action forbidden: {message:?}")
+                            Err(error) => {
+                                eprintln!("Action forbidden: {error:?}");
+                                if let Some(mut events) =
+                                    world.get_resource_mut::<Events<ActionRejected>>()
+                                {
+                                    events.send(ActionRejected { action, error });
+                                }
                             }
                         }
                     }
@@ -174,22 +406,115 @@ pub struct EffectExecutor;
 impl Stage for EffectExecutor {
     fn run(&mut self, world: &mut World) {
         world.resource_scope(|world, mut state: Mut<TurnState>| {
-            if let TurnState::Executing { effects, .. } = state.as_mut() {
-                world.resource_scope(|world, mut systems: Mut<TurnSystems>| loop {
-                    if let Some(effect) = effects.pop() {
-                        println!("Running effect {effect:?}");
-                        systems.run_effect_system(effect, world);
-                    } else {
-                        break;
+            if let TurnState::Executing { action, effects } = state.as_mut() {
+                let mut executed = EffectQueue::default();
+                let mut inverse = Some(EffectQueue::default());
+                // undoing and redoing have to put the world back exactly as it was, without
+                // anything reacting to it
+                let reacting = !action.is::<UndoAction>() && !action.is::<RedoAction>();
+
+                world.resource_scope(|world, mut systems: Mut<TurnSystems>| {
+                    while let Some(effect) = effects.pop() {
+                        run_effect(
+                            effect,
+                            0,
+                            reacting,
+                            &mut systems,
+                            world,
+                            &mut executed,
+                            &mut inverse,
+                        );
                     }
                 });
 
+                if let Some(mut history) = world.get_resource_mut::<UndoHistory>() {
+                    history.record(action, executed, inverse);
+                }
+                if let Some(mut version) = world.get_resource_mut::<TurnVersion>() {
+                    *version = version.next();
+                }
+
                 *state = TurnState::Idle;
             }
         });
     }
 }
 
+// a reaction's follow-up effects may set off further reactions, which could go on forever
+const MAX_REACTION_DEPTH: u32 = 8;
+
+// applies an effect, then straight away whatever follows up on it, adding each to `executed` as it
+// runs; `depth` counts how many reactions led to it
+fn run_effect(
+    effect: AnyEffect,
+    depth: u32,
+    reacting: bool,
+    systems: &mut TurnSystems,
+    world: &mut World,
+    executed: &mut EffectQueue,
+    inverse: &mut Option<EffectQueue>,
+) {
+    if depth > MAX_REACTION_DEPTH {
+        eprintln!("Dropping effect {effect:?}: reactions nested too deeply");
+        return;
+    }
+
+    let reaction = if reacting {
+        systems.react(&effect, world)
+    } else {
+        Reaction::none()
+    };
+    if reaction.seal {
+        *inverse = None;
+    }
+
+    if !reaction.cancel {
+        // the inverse has to be taken from the world before the effect changes it
+        match effect.inverse(world) {
+            Some(undo) => {
+                if let Some(inverse) = inverse {
+                    inverse.push_front(undo);
+                }
+            }
+            None => *inverse = None,
+        }
+
+        println!("Running effect {effect:?}");
+        executed.push(effect.clone());
+        systems.run_effect_system(effect, world);
+    }
+
+    let mut follow_ups = reaction.effects;
+    while let Some(follow_up) = follow_ups.pop() {
+        run_effect(
+            follow_up,
+            depth + 1,
+            reacting,
+            systems,
+            world,
+            executed,
+            inverse,
+        );
+    }
+}
+
+// sent whenever a handler refuses an action, so that whoever asked for it can respond
+pub struct ActionRejected {
+    pub action: AnyAction,
+    pub error: AnyActionError,
+}
+
+// incremented whenever an action's effects have been applied, so that anything worked out from
+// the state of the game, such as which actions are legal, can tell when it is stale
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TurnVersion(u32);
+
+impl TurnVersion {
+    pub fn next(self) -> TurnVersion {
+        TurnVersion(self.0.wrapping_add(1))
+    }
+}
+
 pub enum TurnState {
     Idle,
     Executing {
@@ -211,8 +536,250 @@ impl Plugin for TurnEnginePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TurnSystems>()
             .init_resource::<ActionQueue>()
+            .init_resource::<UndoHistory>()
+            .init_resource::<TurnVersion>()
+            .add_event::<ActionRejected>()
             .insert_resource(TurnState::Idle)
             .add_stage_after(CoreStage::Update, TurnStage::Action, ActionExecutor)
-            .add_stage_after(TurnStage::Action, TurnStage::Effects, EffectExecutor);
+            .add_stage_after(TurnStage::Action, TurnStage::Effects, EffectExecutor)
+            .add_startup_system(setup);
+    }
+}
+
+fn setup(mut systems: ResMut<TurnSystems>) {
+    systems.register_action_handler(undo::undo_handler);
+    systems.register_action_handler(undo::redo_handler);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Counter(u32);
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CountAction(#[serde(with = "entity_serde")] Entity);
+
+    impl Action for CountAction {
+        fn entity(&self) -> Entity {
+            self.0
+        }
+
+        fn cost(&self) -> u8 {
+            0
+        }
+
+        fn describe(&self) -> String {
+            "Count".to_string()
+        }
+    }
+
+    // tries to change the world behind the turn engine's back
+    fn sneaky_handler(
+        In(CountAction(entity)): In<CountAction>,
+        counters: Query<&Counter>,
+        mut commands: Commands,
+    ) -> ActionResult {
+        let Counter(count) = counters.get(entity)?;
+        commands.entity(entity).insert(Counter(count + 1));
+        Ok(EffectQueue::default())
+    }
+
+    fn writing_handler(
+        In(CountAction(entity)): In<CountAction>,
+        mut counters: Query<&mut Counter>,
+    ) -> ActionResult {
+        counters.get_mut(entity)?.0 += 1;
+        Ok(EffectQueue::default())
+    }
+
+    #[test]
+    fn validating_an_action_leaves_the_world_unchanged() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Counter(0)).id();
+        let mut systems = TurnSystems::default();
+        systems.register_action_handler(sneaky_handler);
+
+        for _ in 0..2 {
+            let result = systems.validate_action(&CountAction(entity).into(), &mut world);
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(world.get::<Counter>(entity), Some(&Counter(0)));
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CountEffect(#[serde(with = "entity_serde")] Entity);
+
+    impl Effect for CountEffect {
+        fn inverse(&self, world: &World) -> Option<AnyEffect> {
+            let &Counter(count) = world.get::<Counter>(self.0)?;
+            Some(SetCountEffect(self.0, count).into())
+        }
+    }
+
+    fn count_handler(In(CountEffect(entity)): In<CountEffect>, mut counters: Query<&mut Counter>) {
+        if let Ok(mut counter) = counters.get_mut(entity) {
+            counter.0 += 1;
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SetCountEffect(#[serde(with = "entity_serde")] Entity, u32);
+
+    impl Effect for SetCountEffect {
+        fn inverse(&self, world: &World) -> Option<AnyEffect> {
+            let &Counter(count) = world.get::<Counter>(self.0)?;
+            Some(SetCountEffect(self.0, count).into())
+        }
+    }
+
+    fn set_count_handler(
+        In(SetCountEffect(entity, count)): In<SetCountEffect>,
+        mut counters: Query<&mut Counter>,
+    ) {
+        if let Ok(mut counter) = counters.get_mut(entity) {
+            counter.0 = count;
+        }
+    }
+
+    fn count_action_handler(In(CountAction(entity)): In<CountAction>) -> ActionResult {
+        Ok(EffectQueue::new(CountEffect(entity)))
+    }
+
+    // whoever counts is copied by the entity it names
+    #[derive(Component)]
+    struct CopiedBy(Entity);
+
+    fn copy(In(CountEffect(entity)): In<CountEffect>, copiers: Query<&CopiedBy>) -> Reaction {
+        match copiers.get(entity) {
+            Ok(&CopiedBy(copier)) => Reaction::follow_up(EffectQueue::new(CountEffect(copier))),
+            Err(_) => Reaction::none(),
+        }
+    }
+
+    fn ambush(In(CountEffect(entity)): In<CountEffect>) -> Reaction {
+        Reaction::sealing(EffectQueue::new(SetCountEffect(entity, 0)))
+    }
+
+    // reacts to every count by counting again
+    fn echo(In(effect): In<CountEffect>) -> Reaction {
+        Reaction::follow_up(EffectQueue::new(effect))
+    }
+
+    #[test]
+    fn endless_reactions_are_cut_off() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Counter(0)).id();
+        let mut systems = TurnSystems::default();
+        systems.register_effect_handler(count_handler);
+        systems.register_reaction(echo);
+
+        let mut inverse = Some(EffectQueue::default());
+        run_effect(
+            CountEffect(entity).into(),
+            0,
+            true,
+            &mut systems,
+            &mut world,
+            &mut EffectQueue::default(),
+            &mut inverse,
+        );
+
+        assert_eq!(
+            world.get::<Counter>(entity),
+            Some(&Counter(MAX_REACTION_DEPTH + 1))
+        );
+    }
+
+    fn execute(world: &mut World, action: impl Into<AnyAction>) {
+        let action = action.into();
+        let effects = world
+            .resource_scope(|world, mut systems: Mut<TurnSystems>| {
+                systems.validate_action(&action, world)
+            })
+            .unwrap();
+        world.insert_resource(TurnState::Executing { action, effects });
+        EffectExecutor.run(world);
+    }
+
+    #[test]
+    fn redo_replays_what_reacted_without_reacting_again() {
+        let mut world = World::new();
+        let copier = world.spawn().insert(Counter(0)).id();
+        let entity = world
+            .spawn()
+            .insert_bundle((Counter(0), CopiedBy(copier)))
+            .id();
+
+        let mut systems = TurnSystems::default();
+        systems.register_action_handler(count_action_handler);
+        systems.register_action_handler(undo::undo_handler);
+        systems.register_action_handler(undo::redo_handler);
+        systems.register_effect_handler(count_handler);
+        systems.register_effect_handler(set_count_handler);
+        systems.register_reaction(copy);
+        world.insert_resource(systems);
+        world.insert_resource(UndoHistory::default());
+
+        let counts =
+            |world: &World| [entity, copier].map(|e| world.get::<Counter>(e).map(|c| c.0).unwrap());
+
+        execute(&mut world, CountAction(entity));
+        assert_eq!(counts(&world), [1, 1]);
+
+        for _ in 0..2 {
+            execute(&mut world, UndoAction::new(entity));
+            assert_eq!(counts(&world), [0, 0]);
+            execute(&mut world, RedoAction::new(entity));
+            assert_eq!(counts(&world), [1, 1]);
+        }
+    }
+
+    #[test]
+    fn sealing_reactions_cannot_be_undone() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Counter(0)).id();
+        let mut systems = TurnSystems::default();
+        systems.register_effect_handler(count_handler);
+        systems.register_effect_handler(set_count_handler);
+
+        let mut inverse = Some(EffectQueue::default());
+        run_effect(
+            CountEffect(entity).into(),
+            0,
+            true,
+            &mut systems,
+            &mut world,
+            &mut EffectQueue::default(),
+            &mut inverse,
+        );
+        assert!(inverse.is_some());
+
+        systems.register_reaction(ambush);
+        run_effect(
+            CountEffect(entity).into(),
+            0,
+            true,
+            &mut systems,
+            &mut world,
+            &mut EffectQueue::default(),
+            &mut inverse,
+        );
+        assert!(inverse.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "may only read the world")]
+    fn action_handlers_may_not_write() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Counter(0)).id();
+        let mut systems = TurnSystems::default();
+        systems.register_action_handler(writing_handler);
+
+        let _ = systems.validate_action(&CountAction(entity).into(), &mut world);
     }
 }
@@ -0,0 +1,49 @@
+use super::effects::EffectQueue;
+
+// what a reaction wants done about the effect it observed; the follow-up effects run
+// straight after it, or in its place if it is cancelled
+#[derive(Default)]
+pub struct Reaction {
+    pub cancel: bool,
+    pub effects: EffectQueue,
+    // the acting entity learns something from this they could not take back, e.g. that someone
+    // was lying in wait, so the action can no longer be undone
+    pub seal: bool,
+}
+
+impl Reaction {
+    pub fn none() -> Reaction {
+        Reaction::default()
+    }
+
+    pub fn follow_up(effects: EffectQueue) -> Reaction {
+        Reaction {
+            cancel: false,
+            effects,
+            seal: false,
+        }
+    }
+
+    pub fn cancel(effects: EffectQueue) -> Reaction {
+        Reaction {
+            cancel: true,
+            effects,
+            seal: false,
+        }
+    }
+
+    // a follow-up by someone other than the acting entity
+    pub fn sealing(effects: EffectQueue) -> Reaction {
+        Reaction {
+            cancel: false,
+            effects,
+            seal: true,
+        }
+    }
+
+    pub(super) fn merge(&mut self, other: Reaction) {
+        self.cancel |= other.cancel;
+        self.seal |= other.seal;
+        self.effects.append(other.effects);
+    }
+}
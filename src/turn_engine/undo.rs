@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    actions::{Action, ActionResult, AnyAction, AnyActionError},
+    effects::EffectQueue,
+    entity_serde,
+};
+
+// every effect an executed action ran, including what reacted to it, along with the effects which
+// reverse them
+#[derive(Clone)]
+struct UndoEntry {
+    effects: EffectQueue,
+    inverse: EffectQueue,
+}
+
+// the reversible actions taken by one entity since it last did something irreversible
+#[derive(Default)]
+pub struct UndoHistory {
+    owner: Option<Entity>,
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
+impl UndoHistory {
+    // `inverse` is `None` when any of the executed effects cannot be undone; redoing replays
+    // exactly what ran the first time, so the recorded inverse still holds afterwards
+    pub fn record(
+        &mut self,
+        action: &AnyAction,
+        effects: EffectQueue,
+        inverse: Option<EffectQueue>,
+    ) {
+        if action.is::<UndoAction>() {
+            if let Some(entry) = self.undo.pop() {
+                self.redo.push(entry);
+            }
+            return;
+        }
+        if action.is::<RedoAction>() {
+            if let Some(entry) = self.redo.pop() {
+                self.undo.push(entry);
+            }
+            return;
+        }
+
+        let entity = action.entity();
+        if self.owner != Some(entity) {
+            self.seal();
+            self.owner = Some(entity);
+        }
+
+        self.redo.clear();
+        match inverse {
+            Some(inverse) => self.undo.push(UndoEntry { effects, inverse }),
+            None => self.undo.clear(),
+        }
+    }
+
+    // forgets everything, e.g. once undoing would hide something the player has already learned
+    pub fn seal(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self, entity: Entity) -> bool {
+        self.owner == Some(entity) && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self, entity: Entity) -> bool {
+        self.owner == Some(entity) && !self.redo.is_empty()
+    }
+
+    fn next_undo(&self, entity: Entity) -> Option<&UndoEntry> {
+        self.undo.last().filter(|_| self.owner == Some(entity))
+    }
+
+    fn next_redo(&self, entity: Entity) -> Option<&UndoEntry> {
+        self.redo.last().filter(|_| self.owner == Some(entity))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoAction(#[serde(with = "entity_serde")] Entity);
+
+impl UndoAction {
+    pub fn new(entity: Entity) -> UndoAction {
+        UndoAction(entity)
+    }
+}
+
+impl Action for UndoAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
+    fn cost(&self) -> u8 {
+        0
+    }
+
+    fn describe(&self) -> String {
+        "Undo".to_string()
+    }
+}
+
+pub fn undo_handler(
+    In(UndoAction(entity)): In<UndoAction>,
+    history: Res<UndoHistory>,
+) -> ActionResult {
+    match history.next_undo(entity) {
+        Some(entry) => Ok(entry.inverse.clone()),
+        None => AnyActionError::res_generic("Nothing to undo"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedoAction(#[serde(with = "entity_serde")] Entity);
+
+impl RedoAction {
+    pub fn new(entity: Entity) -> RedoAction {
+        RedoAction(entity)
+    }
+}
+
+impl Action for RedoAction {
+    fn entity(&self) -> Entity {
+        self.0
+    }
+
+    fn cost(&self) -> u8 {
+        0
+    }
+
+    fn describe(&self) -> String {
+        "Redo".to_string()
+    }
+}
+
+pub fn redo_handler(
+    In(RedoAction(entity)): In<RedoAction>,
+    history: Res<UndoHistory>,
+) -> ActionResult {
+    match history.next_redo(entity) {
+        Some(entry) => Ok(entry.effects.clone()),
+        None => AnyActionError::res_generic("Nothing to redo"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex2d::Angle;
+
+    use crate::{
+        ai::AiPlugin,
+        domain::{
+            actions::{end_turn::EndTurnAction, rotate::RotateAction, step::StepAction},
+            common::{Actor, Facing, HexPos},
+        },
+        turn_engine::actions::ActionQueue,
+        Player, RunParams, Scenario, SimulationPlugins,
+    };
+
+    use super::*;
+
+    fn player_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins_with(SimulationPlugins, |group| group.disable::<AiPlugin>());
+        RunParams {
+            scenario: Scenario::Arena,
+            seed: Some(7),
+        }
+        .configure(&mut app);
+        app.update();
+
+        let player = app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(&app.world)
+            .next()
+            .unwrap();
+        (app, player)
+    }
+
+    fn act(app: &mut App, action: impl Into<AnyAction>) {
+        app.world
+            .get_resource_mut::<ActionQueue>()
+            .unwrap()
+            .push_any(action.into());
+        app.update();
+    }
+
+    fn state(app: &App, player: Entity) -> (HexPos, i32, u8) {
+        let pos = app.world.get::<HexPos>(player).unwrap().clone();
+        let facing = app.world.get::<Facing>(player).unwrap().0 as i32;
+        let energy = app.world.get::<Actor>(player).unwrap().actions_remaining;
+        (pos, facing, energy)
+    }
+
+    fn can_undo(app: &App, player: Entity) -> bool {
+        let history = app.world.get_resource::<UndoHistory>().unwrap();
+        history.can_undo(player)
+    }
+
+    #[test]
+    fn undo_restores_state_and_redo_reapplies() {
+        let (mut app, player) = player_app();
+        let start = state(&app, player);
+
+        act(&mut app, RotateAction::new(player, Angle::Right));
+        act(&mut app, StepAction::new(player));
+        let moved = state(&app, player);
+
+        act(&mut app, UndoAction::new(player));
+        act(&mut app, UndoAction::new(player));
+        assert_eq!(state(&app, player), start);
+
+        act(&mut app, RedoAction::new(player));
+        act(&mut app, RedoAction::new(player));
+        assert_eq!(state(&app, player), moved);
+    }
+
+    #[test]
+    fn ending_the_turn_clears_history() {
+        let (mut app, player) = player_app();
+
+        act(&mut app, RotateAction::new(player, Angle::Left));
+        assert!(can_undo(&app, player));
+
+        act(&mut app, EndTurnAction::new(player));
+        assert!(!can_undo(&app, player));
+    }
+}
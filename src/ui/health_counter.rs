@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::{
+    domain::{combat::Ammo, health::Health},
+    Player,
+};
+
+pub struct HealthCounterPlugin;
+
+impl Plugin for HealthCounterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_ui)
+            .add_system(update_player_health)
+            .add_system(update_player_ammo);
+    }
+}
+
+fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: Default::default(),
+                        style: TextStyle {
+                            font: font.clone(),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    TextSection {
+                        value: Default::default(),
+                        style: TextStyle {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PlayerHealthText);
+}
+
+fn update_player_health(
+    player: Query<&Health, (Changed<Health>, With<Player>)>,
+    mut ui: Query<&mut Text, With<PlayerHealthText>>,
+) {
+    if let Ok(mut text) = ui.get_single_mut() {
+        if let Ok(health) = player.get_single() {
+            text.sections[0].value = format!("{}/{} health", health.current, health.max);
+            text.sections[0].style.color = if (health.current as u16) * 3 <= health.max as u16 {
+                Color::RED
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
+
+fn update_player_ammo(
+    player: Query<&Ammo, (Changed<Ammo>, With<Player>)>,
+    mut ui: Query<&mut Text, With<PlayerHealthText>>,
+) {
+    if let Ok(mut text) = ui.get_single_mut() {
+        if let Ok(ammo) = player.get_single() {
+            text.sections[1].value = format!("\n{}/{} ammo", ammo.current, ammo.max);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PlayerHealthText;
@@ -1,9 +1,14 @@
 pub mod energy_counter;
+pub mod health_counter;
 pub mod move_list;
+pub mod rejection_flash;
 
 use bevy::prelude::*;
 
-use self::{energy_counter::EnergyCounterPlugin, move_list::MoveListPlugin};
+use self::{
+    energy_counter::EnergyCounterPlugin, health_counter::HealthCounterPlugin,
+    move_list::MoveListPlugin, rejection_flash::RejectionFlashPlugin,
+};
 
 pub struct UIPlugin;
 
@@ -11,7 +16,9 @@ impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup_ui)
             .add_plugin(MoveListPlugin)
-            .add_plugin(EnergyCounterPlugin);
+            .add_plugin(EnergyCounterPlugin)
+            .add_plugin(HealthCounterPlugin)
+            .add_plugin(RejectionFlashPlugin);
     }
 }
 
@@ -1,25 +1,30 @@
 use bevy::prelude::*;
 
+use crate::{
+    domain::{actions::shoot::ShootAction, turn_queue::TurnQueue},
+    input_map::{Binding, InputMap},
+    intention::PlayerControlled,
+    turn_engine::{TurnState, TurnSystems, TurnVersion},
+};
+
 pub struct MoveListPlugin;
 
 impl Plugin for MoveListPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_ui);
+        app.init_resource::<MoveListKey>()
+            .add_startup_system(setup_ui)
+            .add_system(update_move_list.exclusive_system());
     }
 }
 
+const AVAILABLE_COLOR: Color = Color::WHITE;
+const UNAVAILABLE_COLOR: Color = Color::GRAY;
+
+struct MoveListFont(Handle<Font>);
+
 fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
 
-    let text = vec![
-        "Up: Step forward (1 energy)",
-        "Back: Step back (2 energy)",
-        "Left: Turn left (0 energy)",
-        "Right: Turn right (0 energy)",
-        "Space: Strike (1 energy, end turn)",
-    ]
-    .join("\n");
-
     commands
         .spawn_bundle(TextBundle {
             style: Style {
@@ -32,21 +37,117 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 ..Default::default()
             },
-            text: Text {
-                sections: vec![TextSection {
-                    value: text,
-                    style: TextStyle {
-                        font: font.clone(),
-                        font_size: 16.0,
-                        color: Color::WHITE,
-                    },
-                }],
-                ..Default::default()
-            },
             ..Default::default()
         })
         .insert(MoveListText);
+
+    commands.insert_resource(MoveListFont(font));
 }
 
 #[derive(Component)]
 pub struct MoveListText;
+
+// what the move list was last worked out for; validating every binding is too slow to do each frame
+#[derive(Default)]
+struct MoveListKey(Option<(TurnVersion, Option<Entity>)>);
+
+// lists the bindings in the input map alongside the actions they produce, greying out those
+// which would currently be rejected
+fn update_move_list(world: &mut World) {
+    if !matches!(world.get_resource::<TurnState>(), Some(TurnState::Idle)) {
+        return;
+    }
+
+    let player = world
+        .get_resource::<TurnQueue>()
+        .and_then(|queue| queue.head().copied())
+        .filter(|&e| world.get::<PlayerControlled>(e).is_some());
+
+    let version = world
+        .get_resource::<TurnVersion>()
+        .copied()
+        .unwrap_or_default();
+    let key = Some((version, player));
+    match world.get_resource_mut::<MoveListKey>() {
+        Some(mut shown) if shown.0 != key => shown.0 = key,
+        _ => return,
+    }
+
+    let lines: Vec<(String, Color)> = match player {
+        Some(player) => world.resource_scope(|world, mut systems: Mut<TurnSystems>| {
+            let input_map = world
+                .get_resource::<InputMap>()
+                .cloned()
+                .unwrap_or_default();
+
+            // aiming is only worthwhile when there is something to shoot
+            let can_shoot = systems
+                .generate_actions(player, world)
+                .into_iter()
+                .filter(|action| action.is::<ShootAction>())
+                .any(|action| systems.validate_action(&action, world).is_ok());
+
+            input_map
+                .0
+                .iter()
+                .map(|binding| {
+                    let inputs = binding
+                        .inputs
+                        .iter()
+                        .map(Binding::label)
+                        .collect::<Vec<_>>()
+                        .join(" / ");
+
+                    match binding.intention.to_action(player) {
+                        Some(action) => {
+                            let text = format!(
+                                "{inputs}: {} ({} energy)\n",
+                                action.describe(),
+                                action.cost()
+                            );
+                            if systems.validate_action(&action, world).is_ok() {
+                                (text, AVAILABLE_COLOR)
+                            } else {
+                                (text, UNAVAILABLE_COLOR)
+                            }
+                        }
+                        None if can_shoot => (format!("{inputs}: Aim\n"), AVAILABLE_COLOR),
+                        None => (format!("{inputs}: Aim\n"), UNAVAILABLE_COLOR),
+                    }
+                })
+                .collect()
+        }),
+        None => Vec::new(),
+    };
+
+    let font = match world.get_resource::<MoveListFont>() {
+        Some(font) => font.0.clone(),
+        None => return,
+    };
+
+    let mut texts = world.query_filtered::<&mut Text, With<MoveListText>>();
+    for mut text in texts.iter_mut(world) {
+        let unchanged = text.sections.len() == lines.len()
+            && text
+                .sections
+                .iter()
+                .zip(lines.iter())
+                .all(|(section, (value, color))| {
+                    &section.value == value && section.style.color == *color
+                });
+
+        if !unchanged {
+            text.sections = lines
+                .iter()
+                .map(|(value, color)| TextSection {
+                    value: value.clone(),
+                    style: TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        color: *color,
+                    },
+                })
+                .collect();
+        }
+    }
+}
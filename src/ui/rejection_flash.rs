@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{intention::PlayerControlled, turn_engine::ActionRejected};
+
+// briefly shows why the player's action was refused, just above the player
+pub struct RejectionFlashPlugin;
+
+impl Plugin for RejectionFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(flash_rejections).add_system(fade_flashes);
+    }
+}
+
+#[derive(Component)]
+pub struct RejectionFlash(Timer);
+
+fn flash_rejections(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut rejections: EventReader<ActionRejected>,
+    players: Query<&Transform, With<PlayerControlled>>,
+    flashes: Query<Entity, With<RejectionFlash>>,
+) {
+    for rejection in rejections.iter() {
+        if let Ok(transform) = players.get(rejection.action.entity()) {
+            for e in flashes.iter() {
+                commands.entity(e).despawn();
+            }
+
+            let style = TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 18.0,
+                color: Color::ORANGE_RED,
+            };
+            let alignment = TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            };
+
+            commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(rejection.error.to_string(), style, alignment),
+                    transform: Transform::from_translation(
+                        transform.translation + Vec3::new(0.0, 40.0, 10.0),
+                    ),
+                    ..Default::default()
+                })
+                .insert(RejectionFlash(Timer::from_seconds(1.5, false)));
+        }
+    }
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut RejectionFlash, &mut Text)>,
+) {
+    for (e, mut flash, mut text) in flashes.iter_mut() {
+        flash.0.tick(time.delta());
+
+        if flash.0.finished() {
+            commands.entity(e).despawn();
+        } else {
+            let alpha = flash.0.percent_left();
+            for section in text.sections.iter_mut() {
+                section.style.color.set_a(alpha);
+            }
+        }
+    }
+}